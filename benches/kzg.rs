@@ -1,17 +1,17 @@
 use criterion::measurement::Measurement;
 use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
-use fastcrypto::groups::bls12381::Scalar as BLSScalar;
+use fastcrypto::groups::bls12381::{G1Element, Scalar as BLSScalar};
 use fastcrypto::groups::{GroupElement, Scalar};
 use fastcrypto_kzg::kzg_deriv::KZGDeriv;
 use fastcrypto_kzg::kzg_fk::KZGFK;
 use fastcrypto_kzg::kzg_original::KZGOriginal;
 use fastcrypto_kzg::kzg_tabdfk::KZGTabDFK;
-use fastcrypto_kzg::KZG;
+use fastcrypto_kzg::{VerifierKey, KZG};
 use rand::{thread_rng, Rng};
 
 // Adjust the imports based on your actual project structure
 
-fn kzg_single<K: KZG, M: Measurement>(name: &str, c: &mut BenchmarkGroup<M>) {
+fn kzg_single<K: KZG<G = G1Element>, M: Measurement>(name: &str, c: &mut BenchmarkGroup<M>) {
     let input_sizes = [16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192];
 
     for &size in &input_sizes {
@@ -31,24 +31,24 @@ fn kzg_single<K: KZG, M: Measurement>(name: &str, c: &mut BenchmarkGroup<M>) {
         let open_all_data: Vec<BLSScalar> = (0..size).map(|_| BLSScalar::rand(&mut rng)).collect();
 
         c.bench_function(format!("{}/commit/{}", name, size), |b| {
-            b.iter(|| kzg.commit(&commit_data));
+            b.iter(|| kzg.commit(&commit_data).unwrap());
         });
-        let mut commitment = kzg.commit(&commit_data);
+        let commitment = kzg.commit(&commit_data).unwrap();
 
         // Pick a random index to open
         let index = rng.gen_range(0..size);
 
         // Create an opening
         c.bench_function(format!("{}/open/{}", name, size), |b| {
-            b.iter(|| kzg.open(&commit_data, index));
+            b.iter(|| kzg.open(&commit_data, index).unwrap());
         });
-        let mut open_value = kzg.open(&commit_data, index);
+        let open_value = kzg.open(&commit_data, index).unwrap();
 
         // create all openings
         c.bench_function(format!("{}/open_all/{}", name, size), |b| {
-            b.iter(|| kzg.open_all(&open_all_data));
+            b.iter(|| kzg.open_all(&open_all_data).unwrap());
         });
-        let _open_values = kzg.open_all(&open_all_data);
+        let open_values = kzg.open_all(&open_all_data).unwrap();
 
         // Pick a new index to update
         let mut index_j;
@@ -65,44 +65,53 @@ fn kzg_single<K: KZG, M: Measurement>(name: &str, c: &mut BenchmarkGroup<M>) {
         // Update the commitment
         c.bench_function(format!("{}/update/{}", name, size), |b| {
             b.iter(|| {
-                kzg.update(
-                    &mut commitment,
-                    index_j,
-                    &commit_data[index_j],
-                    &new_v_index_j,
-                )
+                kzg.update(&commitment, index_j, &commit_data[index_j], &new_v_index_j)
+                    .unwrap()
             });
         });
-        let new_commitment = kzg.update(
-            &mut commitment,
-            index_j,
-            &commit_data[index_j],
-            &new_v_index_j,
-        );
+        let new_commitment = kzg
+            .update(&commitment, index_j, &commit_data[index_j], &new_v_index_j)
+            .unwrap();
 
         // Update the opening
         c.bench_function(format!("{}/update_open_j/{}", name, size), |b| {
             b.iter(|| {
                 kzg.update_open_j(
-                    &mut open_value,
+                    &open_value,
                     index,
                     index_j,
                     &commit_data[index_j],
                     &new_v_index_j,
                 )
+                .unwrap()
             });
         });
-        let new_opening = kzg.update_open_j(
-            &mut open_value,
-            index,
-            index_j,
-            &commit_data[index_j],
-            &new_v_index_j,
-        );
+        let new_opening = kzg
+            .update_open_j(
+                &open_value,
+                index,
+                index_j,
+                &commit_data[index_j],
+                &new_v_index_j,
+            )
+            .unwrap();
 
         // Verify the opening
+        let vk = kzg.verifier_key();
         c.bench_function(format!("{}/verify/{}", name, size), |b| {
-            b.iter(|| kzg.verify(index, &commit_data[index], &new_commitment, &new_opening));
+            b.iter(|| vk.verify(index, &commit_data[index], &new_commitment, &new_opening));
+        });
+
+        // Verify all openings at once
+        let open_all_commitment = kzg.commit(&open_all_data).unwrap();
+        let openings: Vec<_> = open_values
+            .into_iter()
+            .take(size)
+            .enumerate()
+            .map(|(i, open_i)| (i, open_all_data[i], open_i))
+            .collect();
+        c.bench_function(format!("{}/verify_batch/{}", name, size), |b| {
+            b.iter(|| vk.verify_batch(&open_all_commitment, &openings, &mut rng));
         });
     }
 }
@@ -0,0 +1,308 @@
+use fastcrypto::encoding::decode_bytes_hex;
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{
+    G1Element, G2Element, G1_ELEMENT_BYTE_LENGTH, G2_ELEMENT_BYTE_LENGTH,
+};
+use fastcrypto::groups::GroupElement;
+use fastcrypto::serde_helpers::ToFromByteArray;
+use serde::Deserialize;
+
+use crate::fft::{bit_reverse_permutation, BLS12381Domain, FFTDomain};
+use crate::srs::SRS;
+
+/// Transcript of the Ethereum KZG ceremony (EIP-4844), as distributed in the `trusted_setup.txt`
+/// format of c-kzg-4844 and the `trusted_setup_4096.json` format of the consensus specs.
+///
+/// The ceremony lists the Lagrange points in bit-reversed order. They are stored here in the
+/// natural order of [BLS12381Domain], so the i-th point is [L_i(tau)]_1 with L_i the Lagrange
+/// polynomial for omega^i. A blob in the consensus specs is indexed in bit-reversed order, so its
+/// k-th element must be placed at the index obtained by reversing the bits of k before committing.
+#[derive(Clone)]
+pub struct EthereumTrustedSetup {
+    g1_monomial: Vec<G1Element>,
+    g1_lagrange: Vec<G1Element>,
+    g2_monomial: Vec<G2Element>,
+}
+
+#[derive(Deserialize)]
+struct JsonTrustedSetup {
+    g1_monomial: Option<Vec<String>>,
+    g1_lagrange: Vec<String>,
+    g2_monomial: Vec<String>,
+}
+
+impl EthereumTrustedSetup {
+    /// Parses a setup in the text format: the number of G1 points and the number of G2 points on
+    /// the first two lines, followed by the G1 Lagrange points, the G2 monomial points and
+    /// optionally the G1 monomial points, one hex encoded compressed point per line.
+    pub fn from_txt(s: &str) -> FastCryptoResult<Self> {
+        let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+        let mut next_count = || -> FastCryptoResult<usize> {
+            lines
+                .next()
+                .and_then(|l| l.parse().ok())
+                .ok_or(FastCryptoError::InvalidInput)
+        };
+        let n_g1 = next_count()?;
+        let n_g2 = next_count()?;
+
+        let lines: Vec<&str> = lines.collect();
+        let g1_monomial = match lines.len() {
+            l if l == n_g1 + n_g2 => None,
+            l if l == 2 * n_g1 + n_g2 => Some(&lines[n_g1 + n_g2..]),
+            _ => return Err(FastCryptoError::InvalidInput),
+        };
+
+        Self::from_hex(g1_monomial, &lines[..n_g1], &lines[n_g1..n_g1 + n_g2])
+    }
+
+    /// Parses a setup in the JSON format with the fields `g1_monomial` (optional), `g1_lagrange`
+    /// and `g2_monomial`, each being a list of hex encoded compressed points.
+    pub fn from_json(s: &str) -> FastCryptoResult<Self> {
+        let setup: JsonTrustedSetup =
+            serde_json::from_str(s).map_err(|_| FastCryptoError::InvalidInput)?;
+        Self::from_hex(
+            setup.g1_monomial.as_deref(),
+            &setup.g1_lagrange,
+            &setup.g2_monomial,
+        )
+    }
+
+    fn from_hex<S: AsRef<str>>(
+        g1_monomial: Option<&[S]>,
+        g1_lagrange: &[S],
+        g2_monomial: &[S],
+    ) -> FastCryptoResult<Self> {
+        let n = g1_lagrange.len();
+        if !n.is_power_of_two() || g2_monomial.len() < 2 {
+            return Err(FastCryptoError::InvalidInput);
+        }
+
+        let mut g1_lagrange = g1_lagrange
+            .iter()
+            .map(|s| decode_g1(s.as_ref()))
+            .collect::<FastCryptoResult<Vec<_>>>()?;
+        bit_reverse_permutation(&mut g1_lagrange);
+
+        let g2_monomial = g2_monomial
+            .iter()
+            .map(|s| decode_g2(s.as_ref()))
+            .collect::<FastCryptoResult<Vec<_>>>()?;
+        if g2_monomial[0] != G2Element::generator() {
+            return Err(FastCryptoError::InvalidInput);
+        }
+
+        // The Lagrange points are the IFFT of the monomial points. If both are given, they must
+        // agree. Older transcripts only contain the Lagrange points, and then the monomial points
+        // are recovered with a group FFT.
+        let domain = BLS12381Domain::new(n)?;
+        let g1_monomial = match g1_monomial {
+            Some(points) if points.len() == n => {
+                let monomial = points
+                    .iter()
+                    .map(|s| decode_g1(s.as_ref()))
+                    .collect::<FastCryptoResult<Vec<_>>>()?;
+                let mut lagrange = monomial.clone();
+                domain.ifft_in_place_group(&mut lagrange);
+                if lagrange != g1_lagrange {
+                    return Err(FastCryptoError::InvalidInput);
+                }
+                monomial
+            }
+            Some(_) => return Err(FastCryptoError::InputLengthWrong(n)),
+            None => {
+                let mut monomial = g1_lagrange.clone();
+                domain.fft_in_place_group(&mut monomial);
+                monomial
+            }
+        };
+        if g1_monomial[0] != G1Element::generator() {
+            return Err(FastCryptoError::InvalidInput);
+        }
+
+        Ok(Self {
+            g1_monomial,
+            g1_lagrange,
+            g2_monomial,
+        })
+    }
+
+    /// Returns the SRS for the powers of tau in this setup.
+    pub fn srs(&self) -> FastCryptoResult<SRS> {
+        SRS::with_g2_powers(self.g1_monomial.clone(), self.g2_monomial.clone())
+    }
+
+    pub fn g1_monomial(&self) -> &[G1Element] {
+        &self.g1_monomial
+    }
+
+    /// The Lagrange points in the natural order of the domain.
+    pub fn g1_lagrange(&self) -> &[G1Element] {
+        &self.g1_lagrange
+    }
+
+    pub fn g2_monomial(&self) -> &[G2Element] {
+        &self.g2_monomial
+    }
+}
+
+/// Decodes a hex encoded compressed G1 point. Deserialization checks that the point is on the
+/// curve and in the prime order subgroup.
+fn decode_g1(s: &str) -> FastCryptoResult<G1Element> {
+    let bytes: [u8; G1_ELEMENT_BYTE_LENGTH] =
+        decode_bytes_hex(s).map_err(|_| FastCryptoError::InvalidInput)?;
+    G1Element::from_byte_array(&bytes)
+}
+
+/// Decodes a hex encoded compressed G2 point. Deserialization checks that the point is on the
+/// curve and in the prime order subgroup.
+fn decode_g2(s: &str) -> FastCryptoResult<G2Element> {
+    let bytes: [u8; G2_ELEMENT_BYTE_LENGTH] =
+        decode_bytes_hex(s).map_err(|_| FastCryptoError::InvalidInput)?;
+    G2Element::from_byte_array(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::encoding::{Encoding, Hex};
+    use fastcrypto::groups::bls12381::Scalar;
+    use fastcrypto::groups::{MultiScalarMul, Scalar as OtherScalar};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+    use crate::kzg_tabdfk::KZGTabDFK;
+    use crate::{ProverKey, VerifierKey, KZG};
+
+    /// Writes the setup for a known tau in the ceremony's point order.
+    fn ceremony_points(n: usize, tau: &Scalar) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let srs = SRS::from_tau(n, tau);
+        let g1_monomial = srs.tau_powers_g1();
+        let mut g1_lagrange = srs
+            .lagrange_basis_g1(&BLS12381Domain::new(n).unwrap())
+            .unwrap();
+        bit_reverse_permutation(&mut g1_lagrange);
+        let g2_monomial = itertools::iterate(G2Element::generator(), |g| g * tau).take(3);
+
+        (
+            g1_monomial
+                .iter()
+                .map(|p| Hex::encode(p.to_byte_array()))
+                .collect(),
+            g1_lagrange
+                .iter()
+                .map(|p| Hex::encode(p.to_byte_array()))
+                .collect(),
+            g2_monomial
+                .map(|p| Hex::encode(p.to_byte_array()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_txt_and_json_agree() {
+        let n = 8;
+        let tau = Scalar::rand(&mut thread_rng());
+        let (g1_monomial, g1_lagrange, g2_monomial) = ceremony_points(n, &tau);
+
+        let txt = format!(
+            "{}\n{}\n{}\n{}\n{}\n",
+            n,
+            g2_monomial.len(),
+            g1_lagrange.join("\n"),
+            g2_monomial.join("\n"),
+            g1_monomial.join("\n")
+        );
+        let from_txt = EthereumTrustedSetup::from_txt(&txt).unwrap();
+
+        // Older text transcripts do not contain the monomial points.
+        let old_txt = txt.lines().take(2 + n + 3).collect::<Vec<_>>().join("\n");
+        let from_old_txt = EthereumTrustedSetup::from_txt(&old_txt).unwrap();
+
+        let json = serde_json::json!({
+            "g1_monomial": g1_monomial.iter().map(|s| format!("0x{}", s)).collect::<Vec<_>>(),
+            "g1_lagrange": g1_lagrange.iter().map(|s| format!("0x{}", s)).collect::<Vec<_>>(),
+            "g2_monomial": g2_monomial.iter().map(|s| format!("0x{}", s)).collect::<Vec<_>>(),
+        })
+        .to_string();
+        let from_json = EthereumTrustedSetup::from_json(&json).unwrap();
+
+        let srs = SRS::from_tau(n, &tau);
+        for setup in [&from_txt, &from_old_txt, &from_json] {
+            assert_eq!(setup.g1_monomial(), srs.tau_powers_g1());
+            assert_eq!(setup.g2_monomial()[1], *srs.g2_tau());
+            assert_eq!(setup.g1_lagrange(), from_txt.g1_lagrange());
+        }
+    }
+
+    #[test]
+    fn test_commitment_matches_ceremony_lagrange_points() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let tau = Scalar::rand(&mut rng);
+        let (g1_monomial, g1_lagrange, g2_monomial) = ceremony_points(n, &tau);
+        let json = serde_json::json!({
+            "g1_monomial": g1_monomial,
+            "g1_lagrange": g1_lagrange,
+            "g2_monomial": g2_monomial,
+        })
+        .to_string();
+        let setup = EthereumTrustedSetup::from_json(&json).unwrap();
+
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let expected = G1Element::multi_scalar_mul(&v, setup.g1_lagrange()).unwrap();
+
+        let srs = setup.srs().unwrap();
+        let kzg_deriv = KZGDeriv::from_srs(n, &srs).unwrap();
+        let kzg_tabdfk = KZGTabDFK::from_srs(n, &srs).unwrap();
+        let commitment = kzg_deriv.commit(&v).unwrap();
+        assert_eq!(commitment.element(), &expected);
+        assert_eq!(kzg_tabdfk.commit(&v).unwrap().element(), &expected);
+
+        let open_value = kzg_deriv.open(&v, 5).unwrap();
+        assert!(kzg_tabdfk
+            .verifier_key()
+            .verify(5, &v[5], &commitment, &open_value));
+    }
+
+    #[test]
+    fn test_invalid_points_are_rejected() {
+        let n = 4;
+        let tau = Scalar::rand(&mut thread_rng());
+        let (g1_monomial, mut g1_lagrange, g2_monomial) = ceremony_points(n, &tau);
+
+        // A valid compressed point with the x-coordinate changed is not on the curve (or, if it
+        // happens to be, almost surely not in the subgroup).
+        let mut bytes = Hex::decode(&g1_lagrange[1]).unwrap();
+        bytes[G1_ELEMENT_BYTE_LENGTH - 1] ^= 1;
+        g1_lagrange[1] = Hex::encode(bytes);
+
+        let json = serde_json::json!({
+            "g1_monomial": g1_monomial,
+            "g1_lagrange": g1_lagrange,
+            "g2_monomial": g2_monomial,
+        })
+        .to_string();
+        assert!(EthereumTrustedSetup::from_json(&json).is_err());
+
+        let truncated = serde_json::json!({
+            "g1_monomial": g1_monomial[..n - 1],
+            "g1_lagrange": g1_lagrange[..n - 1],
+            "g2_monomial": g2_monomial,
+        })
+        .to_string();
+        assert!(EthereumTrustedSetup::from_json(&truncated).is_err());
+
+        // Valid points, but the Lagrange points do not match the monomial points.
+        let (g1_monomial, mut g1_lagrange, g2_monomial) = ceremony_points(n, &tau);
+        g1_lagrange.swap(1, 2);
+        let mismatched = serde_json::json!({
+            "g1_monomial": g1_monomial,
+            "g1_lagrange": g1_lagrange,
+            "g2_monomial": g2_monomial,
+        })
+        .to_string();
+        assert!(EthereumTrustedSetup::from_json(&mismatched).is_err());
+        assert!(EthereumTrustedSetup::from_txt("4\n3\n").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use crate::ProverKey;
+
+/// Defines a wrapper around a group element of the scheme K which also records the size of the
+/// domain it was computed for. Values made by different schemes have different types, and the
+/// verifier rejects values made for a domain of another size.
+macro_rules! scheme_element {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        pub struct $name<K: ProverKey> {
+            element: K::G,
+            size: usize,
+            scheme: PhantomData<fn() -> K>,
+        }
+
+        impl<K: ProverKey> $name<K> {
+            /// Wraps a group element computed for a domain of the given size. Only this crate
+            /// creates these, so the size always matches the domain the element was computed for.
+            /// Received elements are wrapped with
+            /// [crate::verifier_key::KZGVerifierKey::commitment_from_element] and
+            /// [crate::verifier_key::KZGVerifierKey::opening_from_element], which take the size
+            /// from the verifier key.
+            pub(crate) fn new(element: K::G, size: usize) -> Self {
+                Self {
+                    element,
+                    size,
+                    scheme: PhantomData,
+                }
+            }
+
+            pub fn element(&self) -> &K::G {
+                &self.element
+            }
+
+            /// Get the size of the domain this was computed for.
+            pub fn size(&self) -> usize {
+                self.size
+            }
+
+            pub fn into_element(self) -> K::G {
+                self.element
+            }
+        }
+
+        impl<K: ProverKey> Clone for $name<K> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<K: ProverKey> Copy for $name<K> {}
+
+        impl<K: ProverKey> PartialEq for $name<K> {
+            fn eq(&self, other: &Self) -> bool {
+                self.element == other.element && self.size == other.size
+            }
+        }
+
+        impl<K: ProverKey> Eq for $name<K> {}
+
+        impl<K: ProverKey> Debug for $name<K> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("element", &self.element)
+                    .field("size", &self.size)
+                    .finish()
+            }
+        }
+    };
+}
+
+scheme_element!(
+    /// A commitment to a vector, made with the scheme K.
+    Commitment
+);
+
+scheme_element!(
+    /// An opening proving the value at a single index, or the values at a subset of indices, of a
+    /// committed vector, made with the scheme K.
+    Opening
+);
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::groups::bls12381::G1Element;
+    use fastcrypto::groups::GroupElement;
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+
+    #[test]
+    fn test_conversions_and_equality() {
+        let g = G1Element::generator();
+        let commitment = Commitment::<KZGDeriv>::new(g, 8);
+        assert_eq!(commitment.element(), &g);
+        assert_eq!(commitment.size(), 8);
+        assert_eq!(commitment, Commitment::new(g, 8));
+        assert_ne!(commitment, Commitment::new(g, 16));
+        assert_ne!(commitment, Commitment::new(G1Element::zero(), 8));
+        assert_eq!(commitment.into_element(), g);
+    }
+}
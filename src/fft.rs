@@ -1,12 +1,14 @@
-use std::ops::Mul;
+use std::sync::Arc;
 
 use ark_bls12_381::Fr;
 use ark_ff::{BigInteger, PrimeField};
-use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain, MixedRadixEvaluationDomain};
 use fastcrypto::error::{FastCryptoError, FastCryptoResult};
 use fastcrypto::groups::bls12381::Scalar;
 use fastcrypto::groups::{GroupElement, Scalar as ScalarTrait};
 use fastcrypto::serde_helpers::ToFromByteArray;
+use rayon::prelude::*;
+use rayon::ThreadPool;
 
 pub trait FFTDomain: Sized {
     type ScalarType: ScalarTrait;
@@ -20,27 +22,34 @@ pub trait FFTDomain: Sized {
     /// Compute the IFFT of a vector of scalars.
     fn ifft(&self, v_hat: &[Self::ScalarType]) -> Vec<Self::ScalarType>;
 
-    /// Perform a FFT on elements of a group using the domains scalar type.
+    /// Perform a FFT on elements of a group using the domains scalar type. Panics if the length of
+    /// v is not the size of the domain.
     fn fft_in_place_group<G: GroupElement<ScalarType = Self::ScalarType>>(&self, v: &mut [G]) {
-        // TODO: Implement actual in-place algorithm.
-        // TODO: Do we need padding?
-        let result = fft_group(&v, &self.element(1));
-        v.copy_from_slice(&result);
+        fft_group(v, self.elements(), false);
     }
 
-    /// Perform an IFFT on elements of a group using the domains scalar type.
+    /// Perform an IFFT on elements of a group using the domains scalar type. Panics if the length
+    /// of v_hat is not the size of the domain.
     fn ifft_in_place_group<G: GroupElement<ScalarType = Self::ScalarType>>(&self, v_hat: &mut [G]) {
-        let mut result = fft_group(&v_hat, &self.element(self.size() - 1));
+        fft_group(v_hat, self.elements(), true);
         let n_inverse = self.size_inv();
-        for elem in result.iter_mut() {
+        for elem in v_hat.iter_mut() {
             *elem = elem.mul(&n_inverse);
         }
-        v_hat.copy_from_slice(&result);
     }
 
     /// Get the i-th element of the domain which is the n-th root of unity to the index-th power.
-    /// This may be computed on request so it is not suitable for repeated calls.
-    fn element(&self, index: usize) -> Self::ScalarType;
+    fn element(&self, index: usize) -> Self::ScalarType {
+        self.elements()[index % self.size()]
+    }
+
+    /// Get the inverse of the i-th element of the domain.
+    fn inverse_element(&self, index: usize) -> Self::ScalarType {
+        self.element(self.size() - index % self.size())
+    }
+
+    /// Get all the elements of the domain, i.e. the powers of the n-th root of unity in order.
+    fn elements(&self) -> &[Self::ScalarType];
 
     /// Get the size of the domain.
     fn size(&self) -> usize;
@@ -52,6 +61,78 @@ pub trait FFTDomain: Sized {
 #[derive(Clone)]
 pub struct BLS12381Domain {
     domain: GeneralEvaluationDomain<Fr>,
+    // All the elements of the domain, which also serve as the twiddle factors of the group FFTs
+    elements: Arc<Vec<Scalar>>,
+    thread_pool: Option<Arc<ThreadPool>>,
+}
+
+impl BLS12381Domain {
+    /// Creates a domain whose size is the smallest number of the form 2^k or 3 * 2^k which is at
+    /// least n, e.g. exactly n = 6, 12 or 48, instead of rounding up to a power of two. Group and
+    /// scalar FFTs over such domains use the mixed-radix algorithm.
+    ///
+    /// The scalar field of BLS12-381 only has a subgroup of order 3 besides the powers of two that
+    /// arkworks builds domains from, so these are the only mixed-radix sizes. No scheme uses them:
+    /// the schemes create their domains with [FFTDomain::new], which rounds up to a power of two
+    /// for any n up to 2^32, so this is only for computing FFTs directly.
+    pub fn new_mixed_radix(n: usize) -> FastCryptoResult<Self> {
+        let domain =
+            MixedRadixEvaluationDomain::<Fr>::new(n).ok_or(FastCryptoError::InvalidInput)?;
+        Ok(Self::from_arkworks(GeneralEvaluationDomain::MixedRadix(
+            domain,
+        )))
+    }
+
+    fn from_arkworks(domain: GeneralEvaluationDomain<Fr>) -> Self {
+        let omega = arkworks_to_fastcrypto(&domain.group_gen());
+        let elements = itertools::iterate(Scalar::generator(), |x| x * omega)
+            .take(domain.size())
+            .collect();
+        Self {
+            domain,
+            elements: Arc::new(elements),
+            thread_pool: None,
+        }
+    }
+
+    /// Runs the parallel group FFTs, and the schemes' open_all using this domain, in the given
+    /// thread pool instead of the global rayon pool.
+    pub fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Runs op in the thread pool of the domain if one is set, and otherwise in the current one.
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.thread_pool {
+            Some(thread_pool) => thread_pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Like [FFTDomain::fft_in_place_group], but the butterflies of each stage are computed in
+    /// parallel.
+    pub fn fft_in_place_group_parallel<G: GroupElement<ScalarType = Scalar> + Send + Sync>(
+        &self,
+        v: &mut [G],
+    ) {
+        self.install(|| fft_group_in_place_parallel(v, self.elements(), false));
+    }
+
+    /// Like [FFTDomain::ifft_in_place_group], but the butterflies of each stage are computed in
+    /// parallel.
+    pub fn ifft_in_place_group_parallel<G: GroupElement<ScalarType = Scalar> + Send + Sync>(
+        &self,
+        v_hat: &mut [G],
+    ) {
+        self.install(|| {
+            fft_group_in_place_parallel(v_hat, self.elements(), true);
+            let n_inverse = self.size_inv();
+            v_hat
+                .par_iter_mut()
+                .for_each(|elem| *elem = elem.mul(&n_inverse));
+        });
+    }
 }
 
 impl FFTDomain for BLS12381Domain {
@@ -59,33 +140,27 @@ impl FFTDomain for BLS12381Domain {
 
     fn new(n: usize) -> FastCryptoResult<Self> {
         let domain = GeneralEvaluationDomain::<Fr>::new(n).ok_or(FastCryptoError::InvalidInput)?;
-        Ok(Self { domain })
+        Ok(Self::from_arkworks(domain))
     }
 
+    /// Computes the FFT natively over the scalars with the same algorithm as the group FFT. Like in
+    /// arkworks, the input is padded with zeros or truncated to the size of the domain.
     fn fft(&self, v: &[Scalar]) -> Vec<Scalar> {
-        let scalars = v.iter().map(fastcrypto_to_arkworks).collect::<Vec<_>>();
-        let result = self
-            .domain
-            .fft(&scalars)
-            .iter()
-            .map(arkworks_to_fastcrypto)
-            .collect::<Vec<_>>();
-        result
+        let mut v_hat = v.to_vec();
+        v_hat.resize(self.size(), Scalar::zero());
+        self.fft_in_place_group(&mut v_hat);
+        v_hat
     }
 
     fn ifft(&self, v_hat: &[Scalar]) -> Vec<Scalar> {
-        let scalars = v_hat.iter().map(fastcrypto_to_arkworks).collect::<Vec<_>>();
-        let result = self
-            .domain
-            .ifft(&scalars)
-            .iter()
-            .map(arkworks_to_fastcrypto)
-            .collect::<Vec<_>>();
-        result
+        let mut v = v_hat.to_vec();
+        v.resize(self.size(), Scalar::zero());
+        self.ifft_in_place_group(&mut v);
+        v
     }
 
-    fn element(&self, index: usize) -> Scalar {
-        arkworks_to_fastcrypto(&self.domain.element(index))
+    fn elements(&self) -> &[Scalar] {
+        &self.elements
     }
 
     fn size(&self) -> usize {
@@ -97,64 +172,228 @@ impl FFTDomain for BLS12381Domain {
     }
 }
 
-/// Converts a fastcrypto Scalar to an arkworks Fr
-fn fastcrypto_to_arkworks(s: &Scalar) -> Fr {
-    let bytes = s.to_byte_array();
-    Fr::from_be_bytes_mod_order(&bytes)
-}
-
 /// Converts an arkworks Fr to a fastcrypto Scalar
 fn arkworks_to_fastcrypto(f: &Fr) -> Scalar {
     let bytes: [u8; 32] = f.into_bigint().to_bytes_be().try_into().unwrap();
     Scalar::from_byte_array(&bytes).unwrap()
 }
 
-/// Helper function for FFT function for group elements.
-fn fft_group<G: GroupElement>(v: &[G], root_of_unity: &<G as GroupElement>::ScalarType) -> Vec<G> {
-    fft_group_with_offset(v, 0, 1, v.len(), root_of_unity)
+/// Permutes the elements of v such that the element at index i is moved to the index given by
+/// reversing the log2(n) lowest bits of i. The length of v must be a power of two.
+pub(crate) fn bit_reverse_permutation<T>(v: &mut [T]) {
+    let n = v.len();
+    if n <= 2 {
+        return;
+    }
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            v.swap(i, j);
+        }
+    }
+}
+
+/// Computes the FFT of v in place, where the twiddle factors are taken from the n elements of the
+/// domain, or their inverses for an inverse FFT. If n is a power of two, this is done with the
+/// radix-2 algorithm without allocating, and otherwise with the mixed-radix algorithm.
+fn fft_group<G: GroupElement>(v: &mut [G], elements: &[G::ScalarType], inverse: bool) {
+    assert_eq!(
+        v.len(),
+        elements.len(),
+        "The length must be the domain size"
+    );
+    if v.len().is_power_of_two() {
+        fft_group_in_place(v, elements, inverse);
+    } else {
+        let result = fft_group_mixed_radix(v, elements, 1, inverse);
+        v.copy_from_slice(&result);
+    }
+}
+
+/// Computes the FFT of v in place with the iterative radix-2 Cooley-Tukey algorithm: after a bit
+/// reversal permutation, butterflies of size 2, 4, ..., n are applied in turn. The twiddle factors
+/// are taken from the n elements of the domain, or their inverses for an inverse FFT. The length n
+/// of v must be a power of two.
+fn fft_group_in_place<G: GroupElement>(v: &mut [G], elements: &[G::ScalarType], inverse: bool) {
+    let n = v.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(n.is_power_of_two() && elements.len() == n);
+    bit_reverse_permutation(v);
+
+    let mut half_size = 1;
+    while half_size < n {
+        // The stage with blocks of size 2 * half_size uses every stride-th twiddle.
+        let stride = n / (2 * half_size);
+        for j in 0..half_size {
+            let omega = twiddle(elements, j * stride, inverse);
+            for start in (0..n).step_by(2 * half_size) {
+                let even = v[start + j];
+                let odd = if j == 0 {
+                    v[start + j + half_size]
+                } else {
+                    v[start + j + half_size].mul(omega)
+                };
+                v[start + j] = even + odd;
+                v[start + j + half_size] = even - odd;
+            }
+        }
+        half_size *= 2;
+    }
 }
 
-/// Compute the FFT of the elements {v_i} for i = offset, offset + step, offset + 2*step, ..., offset + (n-1)*step
-fn fft_group_with_offset<G: GroupElement>(
+/// Returns omega^index, or omega^-index for an inverse FFT, from the n elements of the domain.
+fn twiddle<S: Copy>(elements: &[S], index: usize, inverse: bool) -> S {
+    if inverse && index != 0 {
+        elements[elements.len() - index]
+    } else {
+        elements[index]
+    }
+}
+
+/// Computes the FFT of v with the recursive mixed-radix Cooley-Tukey algorithm, using the
+/// elements of the domain to the power stride as the root of unity of order n = v.len(). With p the
+/// smallest prime factor of n, the FFTs of the p subsequences v_r, v_{p+r}, ... of size n / p are
+/// combined with a naive DFT of size p. For the domains of [BLS12381Domain::new_mixed_radix], p is
+/// always 2 or 3.
+fn fft_group_mixed_radix<G: GroupElement>(
     v: &[G],
-    offset: usize,
-    step: usize,
-    n: usize,
-    root_of_unity: &<G as GroupElement>::ScalarType,
+    elements: &[G::ScalarType],
+    stride: usize,
+    inverse: bool,
 ) -> Vec<G> {
+    let n = v.len();
     if n <= 1 {
-        return vec![v[offset]];
+        return v.to_vec();
     }
+    let p = smallest_prime_factor(n);
+    let m = n / p;
+
+    let sub_ffts: Vec<Vec<G>> = (0..p)
+        .map(|r| {
+            let v_r: Vec<G> = v.iter().skip(r).step_by(p).copied().collect();
+            fft_group_mixed_radix(&v_r, elements, stride * p, inverse)
+        })
+        .collect();
+
+    let power = |exponent: usize, x: G| {
+        if exponent == 0 {
+            x
+        } else {
+            x.mul(twiddle(elements, exponent * stride, inverse))
+        }
+    };
 
-    // TODO: This implicitly assumes that n is even
-    let half_n = n / 2;
-    let root_of_unity_squared = root_of_unity.mul(root_of_unity);
-    let even_fft = fft_group_with_offset(v, offset, 2 * step, n - half_n, &root_of_unity_squared);
-    let odd_fft = fft_group_with_offset(v, offset + step, 2 * step, half_n, &root_of_unity_squared);
+    // The DFT matrix of size p, whose entry (r, s) is (omega^m)^(r * s) for the p-th root of unity
+    // omega^m, is the same for all k.
+    let dft: Vec<G::ScalarType> = (0..p * p)
+        .map(|e| twiddle(elements, (e / p) * (e % p) % p * m * stride, inverse))
+        .collect();
 
-    let mut omega = G::ScalarType::from(1);
     let mut result = vec![G::zero(); n];
-
-    for i in 0..half_n {
-        let t = odd_fft[i].mul(&omega);
-        result[i] = even_fft[i] + t;
-        result[i + half_n] = even_fft[i] - t;
-        if i < half_n - 1 {
-            omega = root_of_unity.mul(omega);
+    let mut terms = vec![G::zero(); p];
+    for k in 0..m {
+        for (r, term) in terms.iter_mut().enumerate() {
+            *term = power(r * k, sub_ffts[r][k]);
+        }
+        for s in 0..p {
+            result[k + m * s] = terms.iter().enumerate().fold(G::zero(), |sum, (r, t)| {
+                if r * s % p == 0 {
+                    sum + t
+                } else {
+                    sum + t.mul(dft[r * p + s])
+                }
+            });
         }
     }
-
     result
 }
 
+/// Returns the smallest prime factor of n > 1.
+fn smallest_prime_factor(n: usize) -> usize {
+    (2..)
+        .take_while(|p| p * p <= n)
+        .find(|p| n.is_multiple_of(*p))
+        .unwrap_or(n)
+}
+
+/// The number of elements below which the butterflies of a block are not split across threads.
+/// In the early stages of a parallel FFT many small blocks are instead grouped into chunks of this
+/// size, so that each task does enough work to pay for being scheduled.
+const MIN_PARALLEL_BLOCK_SIZE: usize = 64;
+
+/// Computes the FFT of v in place like [fft_group_in_place], but in parallel. In each stage the
+/// blocks are processed in parallel, and the butterflies within a block too once blocks have at
+/// least [MIN_PARALLEL_BLOCK_SIZE] elements. Mixed-radix FFTs are computed sequentially.
+fn fft_group_in_place_parallel<G: GroupElement + Send + Sync>(
+    v: &mut [G],
+    elements: &[G::ScalarType],
+    inverse: bool,
+) where
+    G::ScalarType: Send + Sync,
+{
+    let n = v.len();
+    assert_eq!(n, elements.len(), "The length must be the domain size");
+    if n <= 1 {
+        return;
+    }
+    if !n.is_power_of_two() {
+        return fft_group(v, elements, inverse);
+    }
+    bit_reverse_permutation(v);
+
+    let butterfly = |j: usize, stride: usize, even: &mut G, odd: &mut G| {
+        let t = if j == 0 {
+            *odd
+        } else {
+            odd.mul(twiddle(elements, j * stride, inverse))
+        };
+        *odd = *even - t;
+        *even += t;
+    };
+
+    let mut half_size = 1;
+    while half_size < n {
+        let stride = n / (2 * half_size);
+        if 2 * half_size < MIN_PARALLEL_BLOCK_SIZE {
+            v.par_chunks_mut(MIN_PARALLEL_BLOCK_SIZE).for_each(|chunk| {
+                for block in chunk.chunks_mut(2 * half_size) {
+                    let (evens, odds) = block.split_at_mut(half_size);
+                    for (j, (even, odd)) in evens.iter_mut().zip(odds).enumerate() {
+                        butterfly(j, stride, even, odd);
+                    }
+                }
+            });
+        } else {
+            v.par_chunks_mut(2 * half_size).for_each(|block| {
+                let (evens, odds) = block.split_at_mut(half_size);
+                evens
+                    .par_iter_mut()
+                    .zip(odds.par_iter_mut())
+                    .enumerate()
+                    .for_each(|(j, (even, odd))| butterfly(j, stride, even, odd));
+            });
+        }
+        half_size *= 2;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Mul;
+    use std::sync::Arc;
+
+    use ark_bls12_381::Fr;
+    use ark_ff::PrimeField;
+    use ark_poly::EvaluationDomain;
 
     use fastcrypto::groups::bls12381::{G1Element, Scalar};
     use fastcrypto::groups::{GroupElement, Scalar as OtherScalar};
+    use fastcrypto::serde_helpers::ToFromByteArray;
 
-    use crate::fft::{BLS12381Domain, FFTDomain};
+    use crate::fft::{arkworks_to_fastcrypto, bit_reverse_permutation, BLS12381Domain, FFTDomain};
 
     #[test]
     fn test_fft() {
@@ -170,6 +409,37 @@ mod tests {
         assert_eq!(v, v_prime);
     }
 
+    /// Converts a fastcrypto Scalar to an arkworks Fr
+    fn fastcrypto_to_arkworks(s: &Scalar) -> Fr {
+        Fr::from_be_bytes_mod_order(&s.to_byte_array())
+    }
+
+    #[test]
+    fn test_fft_matches_arkworks() {
+        let mut rng = rand::thread_rng();
+        for (n, length) in [(1, 1), (2, 2), (8, 8), (32, 32), (32, 5)] {
+            let domain = BLS12381Domain::new(n).unwrap();
+            let v: Vec<Scalar> = (0..length).map(|_| OtherScalar::rand(&mut rng)).collect();
+            let v_ark: Vec<Fr> = v.iter().map(fastcrypto_to_arkworks).collect();
+
+            let expected: Vec<Scalar> = domain
+                .domain
+                .fft(&v_ark)
+                .iter()
+                .map(arkworks_to_fastcrypto)
+                .collect();
+            assert_eq!(domain.fft(&v), expected);
+
+            let expected: Vec<Scalar> = domain
+                .domain
+                .ifft(&v_ark)
+                .iter()
+                .map(arkworks_to_fastcrypto)
+                .collect();
+            assert_eq!(domain.ifft(&v), expected);
+        }
+    }
+
     #[test]
     fn test_fft_g1() {
         let mut rng = rand::thread_rng();
@@ -185,6 +455,118 @@ mod tests {
         assert_eq!(v_fft_g, expected_v_fft_g);
     }
 
+    #[test]
+    fn test_fft_g1_sizes() {
+        let mut rng = rand::thread_rng();
+        let g = G1Element::generator();
+        for n in [1, 2, 4, 32] {
+            let domain = BLS12381Domain::new(n).unwrap();
+            let v_scalar: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+            let mut v_g: Vec<G1Element> = v_scalar.iter().map(|x| g.mul(x)).collect();
+
+            domain.fft_in_place_group(&mut v_g);
+            let expected: Vec<G1Element> = domain.fft(&v_scalar).iter().map(|x| g.mul(x)).collect();
+            assert_eq!(v_g, expected);
+
+            domain.ifft_in_place_group(&mut v_g);
+            let expected: Vec<G1Element> = v_scalar.iter().map(|x| g.mul(x)).collect();
+            assert_eq!(v_g, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fft_g1_wrong_length() {
+        let domain = BLS12381Domain::new(8).unwrap();
+        domain.fft_in_place_group(&mut vec![G1Element::generator(); 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fft_g1_parallel_wrong_length() {
+        let domain = BLS12381Domain::new(8).unwrap();
+        domain.ifft_in_place_group_parallel(&mut vec![G1Element::generator(); 16]);
+    }
+
+    #[test]
+    fn test_fft_g1_parallel() {
+        let mut rng = rand::thread_rng();
+        let g = G1Element::generator();
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let thread_pool = Arc::new(thread_pool);
+
+        // Below and above the size from which the butterflies of a block run in parallel.
+        for n in [16, 256] {
+            let domain = BLS12381Domain::new(n)
+                .unwrap()
+                .with_thread_pool(thread_pool.clone());
+            let v: Vec<G1Element> = (0..n).map(|_| g.mul(Scalar::rand(&mut rng))).collect();
+
+            let mut expected = v.clone();
+            domain.fft_in_place_group(&mut expected);
+            let mut v_fft = v.clone();
+            domain.fft_in_place_group_parallel(&mut v_fft);
+            assert_eq!(v_fft, expected);
+
+            domain.ifft_in_place_group_parallel(&mut v_fft);
+            assert_eq!(v_fft, v);
+        }
+    }
+
+    #[test]
+    fn test_elements() {
+        let domain = BLS12381Domain::new(8).unwrap();
+        assert_eq!(domain.elements().len(), 8);
+        for (i, omega_i) in domain.elements().iter().enumerate() {
+            assert_eq!(*omega_i, arkworks_to_fastcrypto(&domain.domain.element(i)));
+            assert_eq!(domain.element(i + 8), *omega_i);
+            assert_eq!(domain.inverse_element(i) * omega_i, Scalar::generator());
+        }
+    }
+
+    #[test]
+    fn test_mixed_radix() {
+        let mut rng = rand::thread_rng();
+        let g = G1Element::generator();
+        for n in [3, 6, 12, 48] {
+            let domain = BLS12381Domain::new_mixed_radix(n).unwrap();
+            assert_eq!(domain.size(), n);
+            let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+            let v_ark: Vec<Fr> = v.iter().map(fastcrypto_to_arkworks).collect();
+
+            let v_hat = domain.fft(&v);
+            let expected: Vec<Scalar> = domain
+                .domain
+                .fft(&v_ark)
+                .iter()
+                .map(arkworks_to_fastcrypto)
+                .collect();
+            assert_eq!(v_hat, expected);
+            assert_eq!(domain.ifft(&v_hat), v);
+
+            let mut v_g: Vec<G1Element> = v.iter().map(|x| g.mul(x)).collect();
+            domain.fft_in_place_group_parallel(&mut v_g);
+            let expected: Vec<G1Element> = v_hat.iter().map(|x| g.mul(x)).collect();
+            assert_eq!(v_g, expected);
+            domain.ifft_in_place_group(&mut v_g);
+            let expected: Vec<G1Element> = v.iter().map(|x| g.mul(x)).collect();
+            assert_eq!(v_g, expected);
+        }
+        assert_eq!(BLS12381Domain::new_mixed_radix(7).unwrap().size(), 8);
+    }
+
+    #[test]
+    fn test_bit_reverse_permutation() {
+        let mut v: Vec<usize> = (0..8).collect();
+        bit_reverse_permutation(&mut v);
+        assert_eq!(v, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+        bit_reverse_permutation(&mut v);
+        assert_eq!(v, (0..8).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_ifft_g1() {
         let domain = BLS12381Domain::new(8).unwrap();
@@ -1,14 +1,20 @@
 use std::ops::Mul;
+use std::sync::Arc;
 
-use fastcrypto::error::FastCryptoResult;
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
 use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
-use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing, Scalar as OtherScalar};
-use itertools::iterate;
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Scalar as OtherScalar};
 use rand::thread_rng;
 use rayon::prelude::*;
+use rayon::ThreadPool;
 
+use crate::commitment::{Commitment, Opening};
 use crate::fft::{BLS12381Domain, FFTDomain};
-use crate::KZG;
+use crate::polynomial::{batch_inverse, subset_quotient};
+use crate::serialize::{KeyReader, KeySerialization, KeyWriter, KZG_DERIV};
+use crate::srs::SRS;
+use crate::verifier_key::KZGVerifierKey;
+use crate::{check_index, check_length, check_size, ProverKey, KZG};
 
 /// Adds three vectors element-wise
 fn add_vectors(v1: Vec<G1Element>, v2: Vec<G1Element>, v3: Vec<G1Element>) -> Vec<G1Element> {
@@ -67,202 +73,253 @@ pub struct KZGDeriv {
     g2_tau: G2Element,
     w_vec: Vec<G1Element>,
     u_vec: Vec<G1Element>,
-    omega_powers: Vec<Scalar>,
-    col_e_div_w: Vec<G1Element>
+    col_e_div_w: Vec<G1Element>,
 }
 
 impl KZGDeriv {
-    /// Computes the omega^index element
-    fn element(&self, index: usize) -> Scalar {
-        self.domain.element(index)
-    }
-}
-
-impl KZG for KZGDeriv {
-    type G = G1Element;
+    /// Completes the precomputation given the Lagrange commitments w_vec and the opening hints u_vec
+    fn from_vectors(
+        domain: BLS12381Domain,
+        g2_tau: G2Element,
+        w_vec: Vec<G1Element>,
+        u_vec: Vec<G1Element>,
+    ) -> Self {
+        let n = domain.size();
 
-    /// Creates a new KZGDeriv instance with a random tau
-    fn new(n: usize) -> FastCryptoResult<Self> {
-        let domain = BLS12381Domain::new(n)?;
-
-        let tau = Scalar::rand(&mut thread_rng());
-        let g2_tau = G2Element::generator() * tau;
-
-        // Compute tau^i for i = 0 to n-1
-        let tau_powers_g: Vec<Scalar> = iterate(Scalar::generator(), |g| g * tau).take(n).collect();
-
-        let g = G1Element::generator();
-        let w_vec: Vec<G1Element> = domain
-            .ifft(&tau_powers_g)
-            .iter()
-            .map(|s| g.mul(s))
-            .collect();
-
-        let omega = domain.element(1);
-        let omega_powers: Vec<Scalar> = iterate(Scalar::generator(), |g| g * omega)
-            .take(n)
-            .collect();
-
-        // compute uvec using w_vec - original implementation
-        let u_vec: Vec<G1Element> = (0..n)
-            .map(|i| {
-                let l_i_minus_1 = w_vec[i] - G1Element::generator();
-                let denom = tau - omega_powers[i];
-                let u_i = (l_i_minus_1 / denom).unwrap();
-                u_i
-            })
-            .collect();
-        
         //pre-compute ColEDiv
         let mut col_e_div_w = w_vec.clone();
         domain.fft_in_place_group(&mut col_e_div_w);
         sparse_c_matrix_vector_multiply(&mut col_e_div_w);
         domain.ifft_in_place_group(&mut col_e_div_w);
 
-        Ok(Self {
+        Self {
             domain,
             n,
             g2_tau,
             w_vec,
             u_vec,
-            omega_powers,
             col_e_div_w,
+        }
+    }
+
+    /// Refreshes all n cached openings, e.g. from [ProverKey::open_all], after the value at
+    /// index_j changed. Each opening gets the delta of [ProverKey::update_open_j], or of
+    /// [ProverKey::update_open_i] at index_j, but the inversions are batched into one and the
+    /// openings are updated in parallel, in the thread pool set with [KZG::with_thread_pool].
+    pub fn update_all_openings(
+        &self,
+        openings: &mut [Opening<Self>],
+        index_j: usize,
+        old_v_j: &Scalar,
+        new_v_j: &Scalar,
+    ) -> FastCryptoResult<()> {
+        self.domain.install(|| {
+            check_index(index_j, self.n)?;
+            if openings.len() != self.n {
+                return Err(FastCryptoError::InputLengthWrong(self.n));
+            }
+            for open in openings.iter() {
+                check_size(open.size(), self.n)?;
+            }
+
+            let omega_powers = self.domain.elements();
+            let omega_j = omega_powers[index_j];
+            let differences: Vec<Scalar> = omega_powers
+                .iter()
+                .enumerate()
+                .map(|(i, omega_i)| {
+                    if i == index_j {
+                        Scalar::generator()
+                    } else {
+                        omega_i - omega_j
+                    }
+                })
+                .collect();
+            let inverses = batch_inverse(&differences)?;
+            let delta = old_v_j - new_v_j;
+
+            openings
+                .par_iter_mut()
+                .zip(inverses.par_iter())
+                .enumerate()
+                .for_each(|(i, (open, inverse))| {
+                    let update = if i == index_j {
+                        self.u_vec[index_j] * (-delta)
+                    } else {
+                        let to_mul_1 = delta * inverse;
+                        let to_mul_2 = -to_mul_1 * omega_j * self.domain.inverse_element(i);
+                        self.w_vec[index_j] * to_mul_1 + self.w_vec[i] * to_mul_2
+                    };
+                    *open = Opening::new(*open.element() + update, self.n);
+                });
+            Ok(())
         })
     }
+}
+
+impl KZG for KZGDeriv {
+    type VerifierKey = KZGVerifierKey;
+
+    /// Creates a new KZGDeriv instance with a random tau
+    fn new(n: usize) -> FastCryptoResult<Self> {
+        let n_dom = BLS12381Domain::new(n)?.size();
+        let tau = Scalar::rand(&mut thread_rng());
+        Self::from_srs(n, &SRS::from_tau(n_dom, &tau))
+    }
+
+    /// Creates a new KZGDeriv instance from the powers of tau in the SRS
+    fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self> {
+        let domain = BLS12381Domain::new(n)?;
+        let w_vec = srs.lagrange_basis_g1(&domain)?;
+        let u_vec = srs.lagrange_quotients_g1(&domain)?;
+
+        Ok(Self::from_vectors(domain, *srs.g2_tau(), w_vec, u_vec))
+    }
+
+    fn verifier_key(&self) -> KZGVerifierKey {
+        KZGVerifierKey::new(&self.domain, self.g2_tau)
+    }
+
+    fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.domain = self.domain.with_thread_pool(thread_pool);
+        self
+    }
+}
+
+impl ProverKey for KZGDeriv {
+    type G = G1Element;
+
+    fn domain(&self) -> &BLS12381Domain {
+        &self.domain
+    }
 
     /// Commits to a vector using the KZG commitment scheme
-    fn commit(&self, v: &[Scalar]) -> G1Element {
-        G1Element::multi_scalar_mul(&v, &self.w_vec).unwrap()
+    fn commit(&self, v: &[Scalar]) -> FastCryptoResult<Commitment<Self>> {
+        check_length(v, self.n)?;
+        let commitment = G1Element::multi_scalar_mul(v, &self.w_vec)?;
+        Ok(Commitment::new(commitment, self.n))
     }
 
     /// Opens a KZG commitment at a specific index
-    fn open(&self, v: &[Scalar], index: usize) -> G1Element {
+    fn open(&self, v: &[Scalar], index: usize) -> FastCryptoResult<Opening<Self>> {
+        check_index(index, self.n)?;
+        check_length(v, self.n)?;
+        let omega_powers = self.domain.elements();
         let (mut scalars, v_prime_terms): (Vec<Scalar>, Vec<Scalar>) = v
-            .into_par_iter()
+            .par_iter()
             .enumerate()
-            .into_par_iter()
             .map(|(j, vj)| {
                 if j != index {
-                    let diff_inverse = (self.omega_powers[index] - self.omega_powers[j])
-                        .inverse()
-                        .unwrap();
-                    (
+                    let diff_inverse = (omega_powers[index] - omega_powers[j]).inverse()?;
+                    Ok((
                         (v[index] - vj) * diff_inverse,
-                        vj * self.omega_powers
-                            [(self.domain.size() + j - index) % self.domain.size()]
-                            * diff_inverse,
-                    )
+                        vj * omega_powers[(self.n + j - index) % self.n] * diff_inverse,
+                    ))
                 } else {
-                    (
+                    Ok((
                         Scalar::zero(),
                         vj * (Scalar::from((v.len() - 1) as u128)
-                            / (Scalar::from(2u128) * self.omega_powers[index]))
-                            .unwrap(),
-                    )
+                            / (Scalar::from(2u128) * omega_powers[index]))?,
+                    ))
                 }
             })
-            .collect();
+            .collect::<FastCryptoResult<Vec<_>>>()?
+            .into_iter()
+            .unzip();
 
-        scalars[index] = v_prime_terms.into_iter().reduce(|a, b| a + b).unwrap();
+        scalars[index] = v_prime_terms.into_iter().fold(Scalar::zero(), |a, b| a + b);
 
-        G1Element::multi_scalar_mul(&scalars, &self.w_vec).unwrap()
+        let open = G1Element::multi_scalar_mul(&scalars, &self.w_vec)?;
+        Ok(Opening::new(open, self.n))
     }
 
     /// Opens a KZG commitment at multiple indices
-    fn open_all(&self, v: &[Scalar]) -> Vec<G1Element> {
-        // Compute tau * Dhatv
-        let idftv = self.domain.ifft(&v);
-        let d_msm_idftv: Vec<Scalar> = multiply_d_matrix_by_vector(&idftv);
-        let dhatv = self.domain.fft(&d_msm_idftv);
-        let result1: Vec<G1Element> = self
-            .w_vec
-            .iter()
-            .zip(dhatv.iter())
-            .map(|(a, b)| a.mul(*b))
-            .collect();
-
-        
-        
-        let result2: Vec<G1Element> = self.col_e_div_w.iter()
-        .zip(v.iter())
-        .map(|(a, b)| a.mul(*b))
-        .collect();
-
-        // Compute diadiv.powtau*v
-        let mut diadiv_idft_tau_v: Vec<G1Element> = self
-            .w_vec
-            .iter()
-            .zip(v.iter())
-            .map(|(a, b)| a.mul(*b))
-            .collect();
-        self.domain.fft_in_place_group(&mut diadiv_idft_tau_v);
-        sparse_d_matrix_vector_multiply(&mut diadiv_idft_tau_v);
-        self.domain.ifft_in_place_group(&mut diadiv_idft_tau_v);
-
-        let result3 = diadiv_idft_tau_v;
-
-        let result = add_vectors(result1, result2, result3);
-
-        result
+    fn open_all(&self, v: &[Scalar]) -> FastCryptoResult<Vec<Opening<Self>>> {
+        self.domain.install(|| {
+            check_length(v, self.n)?;
+
+            // Compute tau * Dhatv
+            let idftv = self.domain.ifft(v);
+            let d_msm_idftv: Vec<Scalar> = multiply_d_matrix_by_vector(&idftv);
+            let dhatv = self.domain.fft(&d_msm_idftv);
+            let result1: Vec<G1Element> = self
+                .w_vec
+                .iter()
+                .zip(dhatv.iter())
+                .map(|(a, b)| a.mul(*b))
+                .collect();
+
+            let result2: Vec<G1Element> = self
+                .col_e_div_w
+                .iter()
+                .zip(v.iter())
+                .map(|(a, b)| a.mul(*b))
+                .collect();
+
+            // Compute diadiv.powtau*v
+            let mut diadiv_idft_tau_v: Vec<G1Element> = self
+                .w_vec
+                .iter()
+                .zip(v.iter())
+                .map(|(a, b)| a.mul(*b))
+                .collect();
+            self.domain
+                .fft_in_place_group_parallel(&mut diadiv_idft_tau_v);
+            sparse_d_matrix_vector_multiply(&mut diadiv_idft_tau_v);
+            self.domain
+                .ifft_in_place_group_parallel(&mut diadiv_idft_tau_v);
+
+            let result3 = diadiv_idft_tau_v;
+
+            let result = add_vectors(result1, result2, result3);
+
+            Ok(result
+                .into_iter()
+                .map(|open| Opening::new(open, self.n))
+                .collect())
+        })
     }
 
-    /// Verifies a KZG opening
-    fn verify(
-        &self,
-        index: usize,
-        v_i: &Scalar,
-        commitment: &G1Element,
-        open_i: &G1Element,
-    ) -> bool {
-        let lhs = *commitment - G1Element::generator() * v_i;
-        let rhs = self.g2_tau - G2Element::generator() * self.omega_powers[index];
-
-        lhs.pairing(&G2Element::generator()) == open_i.pairing(&rhs)
+    fn update_hints(&self) -> (&[G1Element], &[G1Element]) {
+        (&self.w_vec, &self.u_vec)
     }
 
-    fn update(
-        &self,
-        commitment: &mut G1Element,
-        index: usize,
-        old_v_i: &Scalar,
-        new_v_i: &Scalar,
-    ) -> G1Element {
-        *commitment + self.w_vec[index].mul(new_v_i - old_v_i)
+    /// The quotient is committed in evaluation form, since only the Lagrange basis is known.
+    fn open_subset(&self, v: &[Scalar], indices: &[usize]) -> FastCryptoResult<Opening<Self>> {
+        let mut quotient = subset_quotient(&self.domain, v, indices)?;
+        quotient.resize(self.n, Scalar::zero());
+        let open = G1Element::multi_scalar_mul(&self.domain.fft(&quotient), &self.w_vec)?;
+        Ok(Opening::new(open, self.n))
     }
+}
 
-    fn update_open_i(
-        &self,
-        open: &G1Element,
-        index: usize,
-        old_v_i: &Scalar,
-        new_v_i: &Scalar,
-    ) -> G1Element {
-        *open + self.u_vec[index].mul(new_v_i - old_v_i)
+impl KeySerialization for KZGDeriv {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = KeyWriter::new(KZG_DERIV, self.domain.size());
+        writer.write_g2(&self.g2_tau);
+        writer.write_g1_vec(&self.w_vec);
+        writer.write_g1_vec(&self.u_vec);
+        writer.write_g1_vec(&self.col_e_div_w);
+        writer.finish()
     }
 
-    fn update_open_j(
-        &self,
-        open: &G1Element,
-        index: usize,
-        index_j: usize,
-        old_v_j: &Scalar,
-        new_v_j: &Scalar,
-    ) -> G1Element {
-        assert_ne!(index, index_j, "index and index_j should be different.");
-
-        let omega_i = self.element(index);
-        let omega_j = self.element(index_j);
-        let omega_i_inverse = self.element(self.n - index);
-
-        let to_mul_1 = (old_v_j - new_v_j) * (omega_i - omega_j).inverse().unwrap();
-        let to_mul_2 = -to_mul_1 * omega_j * omega_i_inverse;
-
-        *open
-            + G1Element::multi_scalar_mul(
-                &[to_mul_1, to_mul_2],
-                &[self.w_vec[index_j], self.w_vec[index]],
-            )
-            .unwrap()
+    fn from_bytes(bytes: &[u8]) -> FastCryptoResult<Self> {
+        let mut reader = KeyReader::new(bytes, KZG_DERIV)?;
+        let domain = reader.domain()?;
+        let g2_tau = reader.read_g2()?;
+        let w_vec = reader.read_g1_vec()?;
+        let u_vec = reader.read_g1_vec()?;
+        let col_e_div_w = reader.read_g1_vec()?;
+        reader.finish()?;
+
+        Ok(Self {
+            n: domain.size(),
+            domain,
+            g2_tau,
+            w_vec,
+            u_vec,
+            col_e_div_w,
+        })
     }
 }
 
@@ -272,6 +329,7 @@ mod tests {
     use rand::Rng;
 
     use super::*;
+    use crate::VerifierKey;
 
     #[test]
     fn test_kzg_commit_open_verify() {
@@ -279,10 +337,12 @@ mod tests {
         let n = 4;
         let kzg = KZGDeriv::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let open_value = kzg.open(&v, index);
-        let is_valid = kzg.verify(index, &v[index], &commitment, &open_value);
+        let open_value = kzg.open(&v, index).unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &commitment, &open_value);
         assert!(is_valid, "Verification of the opening should succeed.");
     }
 
@@ -292,13 +352,19 @@ mod tests {
         let n = 8;
         let kzg = KZGDeriv::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let mut commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let mut open_value = kzg.open(&v, index);
+        let open_value = kzg.open(&v, index).unwrap();
         let new_v_index = Scalar::rand(&mut rng);
-        let new_commitment = kzg.update(&mut commitment, index, &v[index], &new_v_index);
-        let new_opening = kzg.update_open_i(&mut open_value, index, &v[index], &new_v_index);
-        let is_valid = kzg.verify(index, &new_v_index, &new_commitment, &new_opening);
+        let new_commitment = kzg
+            .update(&commitment, index, &v[index], &new_v_index)
+            .unwrap();
+        let new_opening = kzg
+            .update_open_i(&open_value, index, &v[index], &new_v_index)
+            .unwrap();
+        let is_valid =
+            kzg.verifier_key()
+                .verify(index, &new_v_index, &new_commitment, &new_opening);
         assert!(
             is_valid,
             "Verification of the opening after updating should succeed."
@@ -311,9 +377,9 @@ mod tests {
         let n = 8;
         let kzg = KZGDeriv::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let mut commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let mut open_value = kzg.open(&v, index);
+        let open_value = kzg.open(&v, index).unwrap();
 
         let mut index_j;
         loop {
@@ -324,27 +390,59 @@ mod tests {
         }
 
         let new_v_index_j = Scalar::rand(&mut rng);
-        let new_commitment = kzg.update(&mut commitment, index_j, &v[index_j], &new_v_index_j);
-        let new_opening =
-            kzg.update_open_j(&mut open_value, index, index_j, &v[index_j], &new_v_index_j);
-        let is_valid = kzg.verify(index, &v[index], &new_commitment, &new_opening);
+        let new_commitment = kzg
+            .update(&commitment, index_j, &v[index_j], &new_v_index_j)
+            .unwrap();
+        let new_opening = kzg
+            .update_open_j(&open_value, index, index_j, &v[index_j], &new_v_index_j)
+            .unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &new_commitment, &new_opening);
         assert!(
             is_valid,
             "Verification of the opening after updating j's value should succeed."
         );
     }
 
+    #[test]
+    fn test_kzg_save_load() {
+        let mut rng = rand::thread_rng();
+        let n = 8;
+        let kzg = KZGDeriv::new(n).unwrap();
+        let bytes = kzg.to_bytes();
+
+        let path = std::env::temp_dir().join(format!("kzg_deriv_{}.key", rng.gen::<u64>()));
+        kzg.save(&path).unwrap();
+        let loaded = KZGDeriv::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = loaded.commit(&v).unwrap();
+        assert_eq!(commitment, kzg.commit(&v).unwrap());
+        assert_eq!(loaded.open_all(&v).unwrap(), kzg.open_all(&v).unwrap());
+        assert!(loaded
+            .verifier_key()
+            .verify(5, &v[5], &commitment, &kzg.open(&v, 5).unwrap()));
+
+        let mut corrupted = bytes.clone();
+        corrupted[bytes.len() - 40] ^= 1;
+        assert!(KZGDeriv::from_bytes(&corrupted).is_err());
+        assert!(KZGDeriv::load(std::env::temp_dir().join("kzg_deriv_missing.key")).is_err());
+    }
+
     #[test]
     fn test_kzg_commit_open_all() {
         let mut rng = rand::thread_rng();
         let n = 8;
         let kzg = KZGDeriv::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
-        let open_values = kzg.open_all(&v);
+        let commitment = kzg.commit(&v).unwrap();
+        let open_values = kzg.open_all(&v).unwrap();
 
         for (i, open_value) in open_values.iter().enumerate() {
-            let is_valid = kzg.verify(i, &v[i], &commitment, open_value);
+            let is_valid = kzg.verifier_key().verify(i, &v[i], &commitment, open_value);
             assert!(
                 is_valid,
                 "Verification of the opening should succeed for index {}",
@@ -352,4 +450,39 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_update_all_openings() {
+        let mut rng = rand::thread_rng();
+        let n = 8;
+        let kzg = KZGDeriv::new(n).unwrap();
+        let mut v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let mut openings = kzg.open_all(&v).unwrap();
+
+        let index_j = rng.gen_range(0..n);
+        let new_v_j = Scalar::rand(&mut rng);
+        kzg.update_all_openings(&mut openings, index_j, &v[index_j], &new_v_j)
+            .unwrap();
+        v[index_j] = new_v_j;
+        assert_eq!(openings, kzg.open_all(&v).unwrap());
+
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let kzg_with_pool = kzg.clone().with_thread_pool(Arc::new(thread_pool));
+        let new_v_j = Scalar::rand(&mut rng);
+        kzg_with_pool
+            .update_all_openings(&mut openings, index_j, &v[index_j], &new_v_j)
+            .unwrap();
+        v[index_j] = new_v_j;
+        assert_eq!(openings, kzg.open_all(&v).unwrap());
+
+        assert!(kzg
+            .update_all_openings(&mut openings[1..], index_j, &v[index_j], &new_v_j)
+            .is_err());
+        assert!(kzg
+            .update_all_openings(&mut openings, n, &v[index_j], &new_v_j)
+            .is_err());
+    }
 }
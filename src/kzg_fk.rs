@@ -1,12 +1,18 @@
 use std::ops::Mul;
+use std::sync::Arc;
 
-use fastcrypto::error::FastCryptoResult;
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
 use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
-use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing, Scalar as OtherScalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Scalar as OtherScalar};
 use rand::thread_rng;
+use rayon::ThreadPool;
 
+use crate::commitment::{Commitment, Opening};
 use crate::fft::{BLS12381Domain, FFTDomain};
-use crate::KZG;
+use crate::polynomial::subset_quotient;
+use crate::srs::{UpdateHints, SRS};
+use crate::verifier_key::KZGVerifierKey;
+use crate::{check_index, check_length, ProverKey, KZG};
 
 /// Computes the matrix-vector multiplication for testing purposes -
 // this is the function that is currently used for open_all
@@ -40,9 +46,12 @@ fn compute_matrix_vector_multiplication(
 pub fn multiply_toeplitz_with_v(
     coefficients: &[Scalar],
     tau_powers: &[G1Element],
-) -> Vec<G1Element> {
+) -> FastCryptoResult<Vec<G1Element>> {
     let d = coefficients.len();
-    let domain = BLS12381Domain::new(2 * d).unwrap();
+    if d == 0 {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let domain = BLS12381Domain::new(2 * d)?;
     let mut result = vec![G1Element::zero(); d];
 
     // Construct a_2n vector based on Toeplitz matrix properties
@@ -78,7 +87,7 @@ pub fn multiply_toeplitz_with_v(
         result[i] = fft_result[i];
     }
 
-    result
+    Ok(result)
 }
 
 /// Struct for KZG commitments using Feist-Khovratovich technique
@@ -87,41 +96,66 @@ pub struct KZGFK {
     domain: BLS12381Domain,
     tau_powers_g1: Vec<G1Element>,
     g2_tau: G2Element,
+    update_hints: UpdateHints,
 }
 
 impl KZG for KZGFK {
-    type G = G1Element;
+    type VerifierKey = KZGVerifierKey;
 
     /// Creates a new KZGFK instance with a random tau
     fn new(n: usize) -> FastCryptoResult<Self> {
-        let domain = BLS12381Domain::new(n)?;
-        let n_dom = domain.size();
-
+        let n_dom = BLS12381Domain::new(n)?.size();
         let tau = fastcrypto::groups::bls12381::Scalar::rand(&mut thread_rng());
+        Self::from_srs(n, &SRS::from_tau(n_dom, &tau))
+    }
 
-        let tau_powers_g1: Vec<G1Element> =
-            itertools::iterate(G1Element::generator(), |g| g.mul(tau))
-                .take(n_dom)
-                .collect();
-
-        let g2_tau = G2Element::generator().mul(tau);
+    /// Creates a new KZGFK instance from the powers of tau in the SRS
+    fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self> {
+        let domain = BLS12381Domain::new(n)?;
+        let tau_powers_g1 = srs.powers_g1(domain.size())?.to_vec();
 
         Ok(Self {
             domain,
             tau_powers_g1,
-            g2_tau,
+            g2_tau: *srs.g2_tau(),
+            update_hints: UpdateHints::default(),
         })
     }
 
+    fn verifier_key(&self) -> KZGVerifierKey {
+        KZGVerifierKey::new(&self.domain, self.g2_tau)
+    }
+
+    fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.domain = self.domain.with_thread_pool(thread_pool);
+        self
+    }
+}
+
+impl ProverKey for KZGFK {
+    type G = G1Element;
+
+    fn domain(&self) -> &BLS12381Domain {
+        &self.domain
+    }
+
     /// Commits to a vector using the KZG commitment scheme
-    fn commit(&self, v: &[Scalar]) -> G1Element {
+    fn commit(&self, v: &[Scalar]) -> FastCryptoResult<Commitment<Self>> {
+        check_length(v, self.domain.size())?;
         let poly = self.domain.ifft(v);
-        G1Element::multi_scalar_mul(&poly, &self.tau_powers_g1).unwrap()
+        let commitment = G1Element::multi_scalar_mul(&poly, &self.tau_powers_g1)?;
+        Ok(Commitment::new(commitment, self.domain.size()))
     }
 
     /// Opens a KZG commitment at a specific index
-    fn open(&self, v: &[Scalar], index: usize) -> G1Element {
+    fn open(&self, v: &[Scalar], index: usize) -> FastCryptoResult<Opening<Self>> {
+        check_index(index, self.domain.size())?;
+        check_length(v, self.domain.size())?;
         let poly = self.domain.ifft(v);
+        if poly.len() == 1 {
+            // The polynomial is constant, so the quotient is zero.
+            return Ok(Opening::new(G1Element::zero(), 1));
+        }
         let mut quotient_coeffs: Vec<Scalar> = vec![Scalar::zero(); poly.len() - 1];
         quotient_coeffs[poly.len() - 2] = poly[poly.len() - 1];
 
@@ -130,78 +164,49 @@ impl KZG for KZGFK {
             quotient_coeffs[j] = poly[j + 1] + quotient_coeffs[j + 1] * omega_i;
         }
 
-        G1Element::multi_scalar_mul(
+        let open = G1Element::multi_scalar_mul(
             &quotient_coeffs,
             &self.tau_powers_g1[..quotient_coeffs.len()],
-        )
-        .unwrap()
+        )?;
+        Ok(Opening::new(open, self.domain.size()))
     }
 
     /// Opens a KZG commitment at multiple indices
-    fn open_all(&self, v: &[Scalar]) -> Vec<G1Element> {
-        let poly = self.domain.ifft(v);
-        let degree = poly.len() - 1;
-
-        let mut t = self.tau_powers_g1.clone();
-        t.truncate(degree);
-        t.reverse();
-
-        let test_poly = &poly[poly.len() - degree..];
-
-        let h_test = compute_matrix_vector_multiplication(test_poly, &t);
-
-        let mut result = vec![G1Element::zero(); poly.len()];
-        for i in 0..degree {
-            result[i] = h_test[i];
-        }
-
-        self.domain.fft_in_place_group(&mut result);
-        result
-    }
-
-    /// Verifies a KZG opening
-    fn verify(
-        &self,
-        index: usize,
-        v_i: &Scalar,
-        commitment: &G1Element,
-        open_i: &G1Element,
-    ) -> bool {
-        let lhs = *commitment - G1Element::generator() * v_i;
-        let rhs = self.g2_tau - G2Element::generator() * self.domain.element(index);
-
-        lhs.pairing(&G2Element::generator()) == open_i.pairing(&rhs)
-    }
-
-    fn update(
-        &self,
-        commitment: &mut G1Element,
-        _index: usize,
-        _old_v_i: &Scalar,
-        _new_v_i: &Scalar,
-    ) -> G1Element {
-        *commitment
+    fn open_all(&self, v: &[Scalar]) -> FastCryptoResult<Vec<Opening<Self>>> {
+        self.domain.install(|| {
+            check_length(v, self.domain.size())?;
+            let poly = self.domain.ifft(v);
+            let degree = poly.len() - 1;
+
+            let mut t = self.tau_powers_g1.clone();
+            t.truncate(degree);
+            t.reverse();
+
+            let test_poly = &poly[poly.len() - degree..];
+
+            let h_test = compute_matrix_vector_multiplication(test_poly, &t);
+
+            let mut result = vec![G1Element::zero(); poly.len()];
+            for i in 0..degree {
+                result[i] = h_test[i];
+            }
+
+            self.domain.fft_in_place_group_parallel(&mut result);
+            Ok(result
+                .into_iter()
+                .map(|open| Opening::new(open, self.domain.size()))
+                .collect())
+        })
     }
 
-    fn update_open_i(
-        &self,
-        open: &G1Element,
-        _index: usize,
-        _old_v_i: &Scalar,
-        _new_v_i: &Scalar,
-    ) -> G1Element {
-        *open
+    fn update_hints(&self) -> (&[G1Element], &[G1Element]) {
+        self.update_hints.get(&self.domain, &self.tau_powers_g1)
     }
 
-    fn update_open_j(
-        &self,
-        open: &G1Element,
-        _index: usize,
-        _index_j: usize,
-        _old_v_j: &Scalar,
-        _new_v_j: &Scalar,
-    ) -> G1Element {
-        *open
+    fn open_subset(&self, v: &[Scalar], indices: &[usize]) -> FastCryptoResult<Opening<Self>> {
+        let quotient = subset_quotient(&self.domain, v, indices)?;
+        let open = G1Element::multi_scalar_mul(&quotient, &self.tau_powers_g1[..quotient.len()])?;
+        Ok(Opening::new(open, self.domain.size()))
     }
 }
 
@@ -211,6 +216,7 @@ mod tests {
     use rand::Rng;
 
     use super::*;
+    use crate::VerifierKey;
 
     #[test]
     fn test_kzg_commit_open_verify() {
@@ -218,10 +224,12 @@ mod tests {
         let n = 8;
         let kzg = KZGFK::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let open_value = kzg.open(&v, index);
-        let is_valid = kzg.verify(index, &v[index], &commitment, &open_value);
+        let open_value = kzg.open(&v, index).unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &commitment, &open_value);
         assert!(is_valid, "Verification of the opening should succeed.");
     }
 
@@ -230,14 +238,16 @@ mod tests {
         let mut rng = rand::thread_rng();
         let n = 9;
         let kzg = KZGFK::new(n).unwrap();
-        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
-        let mut open_values = kzg.open_all(&v);
+        // The domain has 16 elements, so the vector is padded to fill it.
+        let mut v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        v.resize(16, Scalar::zero());
+        let commitment = kzg.commit(&v).unwrap();
+        let mut open_values = kzg.open_all(&v).unwrap();
 
         open_values.truncate(n);
 
         for (i, open_value) in open_values.iter().enumerate() {
-            let is_valid = kzg.verify(i, &v[i], &commitment, open_value);
+            let is_valid = kzg.verifier_key().verify(i, &v[i], &commitment, open_value);
             assert!(
                 is_valid,
                 "Verification of the opening should succeed for index {}",
@@ -246,6 +256,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_kzg_domain_of_size_one() {
+        let v = [Scalar::rand(&mut thread_rng())];
+        let kzg = KZGFK::new(1).unwrap();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_value = kzg.open(&v, 0).unwrap();
+        assert_eq!(kzg.open_all(&v).unwrap(), vec![open_value]);
+        assert!(kzg
+            .verifier_key()
+            .verify(0, &v[0], &commitment, &open_value));
+
+        assert!(multiply_toeplitz_with_v(&[], &[]).is_err());
+    }
+
     #[test]
     fn test_check_toeplitz() {
         let v_scalar = vec![
@@ -266,7 +290,7 @@ mod tests {
                 .take(8)
                 .collect();
 
-        let h_alin = multiply_toeplitz_with_v(&v_scalar, &tau_powers_g1);
+        let h_alin = multiply_toeplitz_with_v(&v_scalar, &tau_powers_g1).unwrap();
         let h_mult = compute_matrix_vector_multiplication(&v_scalar, &tau_powers_g1);
 
         assert!(h_alin == h_mult, "Toeplitz multiplication mismatch.");
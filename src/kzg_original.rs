@@ -1,45 +1,17 @@
-use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use std::sync::Arc;
+
+use fastcrypto::error::FastCryptoResult;
 use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
-use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing, Scalar as OtherScalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Scalar as OtherScalar};
 use rand::thread_rng;
+use rayon::ThreadPool;
 
+use crate::commitment::{Commitment, Opening};
 use crate::fft::{BLS12381Domain, FFTDomain};
-use crate::KZG;
-
-/// Performs polynomial division of the dividend by the divisor
-/// Returns the quotient and remainder
-fn polynomial_division(
-    dividend: &[Scalar],
-    divisor: &[Scalar],
-) -> FastCryptoResult<(Vec<Scalar>, Vec<Scalar>)> {
-    let mut remainder = Vec::from(dividend);
-
-    let divisor_leading_term_inverse = divisor
-        .last()
-        .ok_or(FastCryptoError::InvalidInput)?
-        .inverse()?;
-
-    let quotient_size = dividend.len() - divisor.len() + 1;
-
-    let mut quotient: Vec<Scalar> = (0..quotient_size)
-        .rev()
-        .map(|i| {
-            let q_i = remainder[i + divisor.len() - 1] * divisor_leading_term_inverse;
-            for j in 0..divisor.len() {
-                remainder[i + j] -= q_i * divisor[j];
-            }
-            q_i
-        })
-        .collect();
-    quotient.reverse();
-
-    // Remove leading zeros in the remainder
-    while remainder.len() > 1 && remainder.last().unwrap() == &Scalar::zero() {
-        remainder.pop();
-    }
-
-    Ok((quotient, remainder))
-}
+use crate::polynomial::{polynomial_division, subset_quotient};
+use crate::srs::{UpdateHints, SRS};
+use crate::verifier_key::KZGVerifierKey;
+use crate::{check_index, check_length, ProverKey, KZG};
 
 /// Struct for the original KZG commitment scheme using BLS12-381
 #[derive(Clone)]
@@ -47,100 +19,86 @@ pub struct KZGOriginal {
     domain: BLS12381Domain,
     tau_powers_g1: Vec<G1Element>,
     g2_tau: G2Element,
+    update_hints: UpdateHints,
 }
 
 impl KZG for KZGOriginal {
-    type G = G1Element;
+    type VerifierKey = KZGVerifierKey;
 
     /// Creates a new KZGOriginal instance with a random tau
     fn new(n: usize) -> FastCryptoResult<Self> {
-        let domain = BLS12381Domain::new(n)?;
-
+        let n_dom = BLS12381Domain::new(n)?.size();
         let tau = fastcrypto::groups::bls12381::Scalar::rand(&mut thread_rng());
+        Self::from_srs(n, &SRS::from_tau(n_dom, &tau))
+    }
 
-        // Compute g^tau^i for i = 0 to n-1 in G1
-        let tau_powers_g1: Vec<G1Element> = itertools::iterate(G1Element::generator(), |g| g * tau)
-            .take(n)
-            .collect();
-
-        let g2_tau = G2Element::generator() * tau;
+    /// Creates a new KZGOriginal instance from the powers of tau in the SRS
+    fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self> {
+        let domain = BLS12381Domain::new(n)?;
+        let tau_powers_g1 = srs.powers_g1(domain.size())?.to_vec();
 
         Ok(Self {
             domain,
             tau_powers_g1,
-            g2_tau,
+            g2_tau: *srs.g2_tau(),
+            update_hints: UpdateHints::default(),
         })
     }
 
-    /// Commits to a vector using the KZG commitment scheme
-    fn commit(&self, v: &[Scalar]) -> G1Element {
-        let poly = self.domain.ifft(&v);
-        G1Element::multi_scalar_mul(poly.as_slice(), &self.tau_powers_g1).unwrap()
+    fn verifier_key(&self) -> KZGVerifierKey {
+        KZGVerifierKey::new(&self.domain, self.g2_tau)
     }
 
-    /// Opens a KZG commitment at a specific index
-    fn open(&self, v: &[Scalar], index: usize) -> G1Element {
-        let mut poly = self.domain.ifft(&v);
-        poly[0] -= &v[index];
+    fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.domain = self.domain.with_thread_pool(thread_pool);
+        self
+    }
+}
 
-        let divisor = [-self.domain.element(index), Scalar::generator()];
-        let (quotient, _) = polynomial_division(&poly, &divisor).unwrap();
+impl ProverKey for KZGOriginal {
+    type G = G1Element;
 
-        G1Element::multi_scalar_mul(&quotient, &self.tau_powers_g1[..quotient.len()]).unwrap()
+    fn domain(&self) -> &BLS12381Domain {
+        &self.domain
     }
 
-    /// Opens a KZG commitment at multiple indices
-    fn open_all(&self, v: &[Scalar]) -> Vec<G1Element> {
-        (0..v.len()).into_iter().map(|i| self.open(v, i)).collect()
+    /// Commits to a vector using the KZG commitment scheme
+    fn commit(&self, v: &[Scalar]) -> FastCryptoResult<Commitment<Self>> {
+        check_length(v, self.domain.size())?;
+        let poly = self.domain.ifft(v);
+        let commitment = G1Element::multi_scalar_mul(poly.as_slice(), &self.tau_powers_g1)?;
+        Ok(Commitment::new(commitment, self.domain.size()))
     }
 
-    /// Verifies a KZG opening
-    fn verify(
-        &self,
-        index: usize,
-        v_i: &Scalar,
-        commitment: &G1Element,
-        open_i: &G1Element,
-    ) -> bool {
-        let lhs = *commitment - G1Element::generator() * v_i;
-
-        let rhs = self.g2_tau - G2Element::generator() * self.domain.element(index);
+    /// Opens a KZG commitment at a specific index
+    fn open(&self, v: &[Scalar], index: usize) -> FastCryptoResult<Opening<Self>> {
+        check_index(index, self.domain.size())?;
+        check_length(v, self.domain.size())?;
+        let mut poly = self.domain.ifft(v);
+        poly[0] -= &v[index];
 
-        let lhs_pairing = lhs.pairing(&G2Element::generator());
-        let rhs_pairing = open_i.pairing(&rhs);
+        let divisor = [-self.domain.element(index), Scalar::generator()];
+        let (quotient, _) = polynomial_division(&poly, &divisor)?;
 
-        lhs_pairing == rhs_pairing
+        let open = G1Element::multi_scalar_mul(&quotient, &self.tau_powers_g1[..quotient.len()])?;
+        Ok(Opening::new(open, self.domain.size()))
     }
 
-    fn update(
-        &self,
-        commitment: &mut G1Element,
-        _index: usize,
-        _old_v_i: &Scalar,
-        _new_v_i: &Scalar,
-    ) -> G1Element {
-        *commitment
+    /// Opens a KZG commitment at multiple indices
+    fn open_all(&self, v: &[Scalar]) -> FastCryptoResult<Vec<Opening<Self>>> {
+        check_length(v, self.domain.size())?;
+        self.domain
+            .install(|| (0..v.len()).map(|i| self.open(v, i)).collect())
     }
 
-    fn update_open_i(
-        &self,
-        open: &G1Element,
-        _index: usize,
-        _old_v_i: &Scalar,
-        _new_v_i: &Scalar,
-    ) -> G1Element {
-        *open
+    fn update_hints(&self) -> (&[G1Element], &[G1Element]) {
+        self.update_hints.get(&self.domain, &self.tau_powers_g1)
     }
 
-    fn update_open_j(
-        &self,
-        open: &G1Element,
-        _index: usize,
-        _index_j: usize,
-        _old_v_j: &Scalar,
-        _new_v_j: &Scalar,
-    ) -> G1Element {
-        *open
+    fn open_subset(&self, v: &[Scalar], indices: &[usize]) -> FastCryptoResult<Opening<Self>> {
+        let quotient = subset_quotient(&self.domain, v, indices)?;
+        let open = G1Element::multi_scalar_mul(&quotient, &self.tau_powers_g1[..quotient.len()])?;
+        Ok(Opening::new(open, self.domain.size()))
     }
 }
 
@@ -150,6 +108,7 @@ mod tests {
     use rand::Rng;
 
     use super::*;
+    use crate::VerifierKey;
 
     #[test]
     fn test_kzg_commit_open_verify() {
@@ -157,10 +116,12 @@ mod tests {
         let n = 8;
         let kzg = KZGOriginal::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let open_value = kzg.open(&v, index);
-        let is_valid = kzg.verify(index, &v[index], &commitment, &open_value);
+        let open_value = kzg.open(&v, index).unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &commitment, &open_value);
         assert!(is_valid, "Verification of the opening should succeed.");
     }
 
@@ -170,12 +131,14 @@ mod tests {
         let n = 8;
         let kzg = KZGOriginal::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let indices: Vec<usize> = (0..n).collect();
-        let open_values = kzg.open_all(&v);
+        let open_values = kzg.open_all(&v).unwrap();
 
         for (i, open_value) in open_values.iter().enumerate() {
-            let is_valid = kzg.verify(indices[i], &v[indices[i]], &commitment, open_value);
+            let is_valid =
+                kzg.verifier_key()
+                    .verify(indices[i], &v[indices[i]], &commitment, open_value);
             assert!(
                 is_valid,
                 "Verification of the opening should succeed for index {}",
@@ -1,13 +1,19 @@
 use std::ops::Mul;
+use std::sync::Arc;
 
 use fastcrypto::error::FastCryptoResult;
 use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
-use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing, Scalar as OtherScalar};
-use itertools::iterate;
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Scalar as OtherScalar};
 use rand::thread_rng;
+use rayon::ThreadPool;
 
+use crate::commitment::{Commitment, Opening};
 use crate::fft::{BLS12381Domain, FFTDomain};
-use crate::KZG;
+use crate::polynomial::subset_quotient;
+use crate::serialize::{KeyReader, KeySerialization, KeyWriter, KZG_TABDFK};
+use crate::srs::{vanishing_quotients_g1, SRS};
+use crate::verifier_key::KZGVerifierKey;
+use crate::{check_index, check_length, ProverKey, KZG};
 
 pub fn build_circulant(polynomial: &[Scalar], size: usize) -> Vec<Scalar> {
     let mut circulant = vec![Scalar::zero(); 2 * size];
@@ -29,11 +35,11 @@ pub fn multiply_toeplitz_with_v(
     polynomial: &[Scalar],
     v: &[G1Element],
     size: usize,
-) -> Vec<G1Element> {
+) -> FastCryptoResult<Vec<G1Element>> {
     let m = polynomial.len() - 1;
     let size = std::cmp::max(size, m);
 
-    let domain = BLS12381Domain::new(2 * size).unwrap();
+    let domain = BLS12381Domain::new(2 * size)?;
 
     let size = domain.size() / 2;
     let mut circulant = build_circulant(polynomial, size);
@@ -49,19 +55,19 @@ pub fn multiply_toeplitz_with_v(
     }
 
     tmp.resize(domain.size(), G1Element::zero());
-    domain.fft_in_place_group(&mut tmp);
+    domain.fft_in_place_group_parallel(&mut tmp);
     let circulant_fft = domain.fft(&mut circulant);
 
     for (i, j) in tmp.iter_mut().zip(circulant_fft.iter()) {
         *i = i.mul(*j);
     }
 
-    domain.ifft_in_place_group(&mut tmp);
+    domain.ifft_in_place_group_parallel(&mut tmp);
     let mut result = vec![G1Element::zero(); size];
     for i in 0..size {
         result[i] = tmp[i];
     }
-    result
+    Ok(result)
 }
 
 #[derive(Clone)]
@@ -75,55 +81,25 @@ pub struct KZGTabDFK {
 }
 
 impl KZG for KZGTabDFK {
-    type G = G1Element;
+    type VerifierKey = KZGVerifierKey;
 
     fn new(n: usize) -> FastCryptoResult<Self> {
+        let n_dom = BLS12381Domain::new(n)?.size();
+        let tau = Scalar::rand(&mut thread_rng());
+        Self::from_srs(n, &SRS::from_tau(n_dom, &tau))
+    }
+
+    fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self> {
         let domain = BLS12381Domain::new(n)?;
-        let n_dom = domain.size();
+        let tau_powers_g1 = srs.powers_g1(domain.size())?.to_vec();
 
-        let tau = Scalar::rand(&mut thread_rng());
-        let g2_tau = G2Element::generator().mul(tau);
-
-        let g_tau_n = (0..n_dom).fold(G1Element::generator(), |acc, _| acc * tau);
-        let a = g_tau_n - G1Element::generator();
-
-        let mut a_vec = vec![G1Element::zero(); n_dom];
-        let mut u_vec = vec![G1Element::zero(); n_dom];
-
-        let tau_powers_g: Vec<Scalar> = iterate(Scalar::generator(), |g| g * tau)
-            .take(n_dom)
-            .collect();
-        let tau_powers_g1: Vec<G1Element> =
-            itertools::iterate(G1Element::generator(), |g| g.mul(tau))
-                .take(n_dom)
-                .collect();
-
-        let g = G1Element::generator();
-        let l_vec: Vec<G1Element> = domain
-            .ifft(&tau_powers_g)
-            .iter()
-            .map(|s| g.mul(s))
-            .collect();
-
-        let mut omega_i = domain.element(0);
-        for i in 0..n_dom {
-            let denom = tau - omega_i;
-            let a_i = a.mul(denom.inverse().unwrap());
-            a_vec[i] = a_i;
-
-            let l_i_minus_1 = l_vec[i] - G1Element::generator();
-            let denom = tau - omega_i;
-            let u_i = l_i_minus_1.mul(denom.inverse().unwrap());
-            u_vec[i] = u_i;
-
-            if i < n_dom - 1 {
-                omega_i *= domain.element(1);
-            }
-        }
+        let l_vec = srs.lagrange_basis_g1(&domain)?;
+        let u_vec = srs.lagrange_quotients_g1(&domain)?;
+        let a_vec = vanishing_quotients_g1(&domain, &l_vec);
 
         Ok(Self {
             domain,
-            g2_tau,
+            g2_tau: *srs.g2_tau(),
             u_vec,
             l_vec,
             a_vec,
@@ -131,102 +107,112 @@ impl KZG for KZGTabDFK {
         })
     }
 
-    fn commit(&self, v: &[Scalar]) -> G1Element {
-        let mut padded_v = vec![Scalar::zero(); self.domain.size()];
-        for i in 0..v.len() {
-            padded_v[i] = v[i];
-        }
-        G1Element::multi_scalar_mul(&padded_v, &self.l_vec).unwrap()
+    fn verifier_key(&self) -> KZGVerifierKey {
+        KZGVerifierKey::new(&self.domain, self.g2_tau)
     }
 
-    fn open(&self, v: &[Scalar], index: usize) -> G1Element {
+    fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.domain = self.domain.with_thread_pool(thread_pool);
+        self
+    }
+}
+
+impl ProverKey for KZGTabDFK {
+    type G = G1Element;
+
+    fn domain(&self) -> &BLS12381Domain {
+        &self.domain
+    }
+
+    fn commit(&self, v: &[Scalar]) -> FastCryptoResult<Commitment<Self>> {
+        check_length(v, self.domain.size())?;
+        let commitment = G1Element::multi_scalar_mul(v, &self.l_vec)?;
+        Ok(Commitment::new(commitment, self.domain.size()))
+    }
+
+    fn open(&self, v: &[Scalar], index: usize) -> FastCryptoResult<Opening<Self>> {
+        check_index(index, self.domain.size())?;
+        check_length(v, self.domain.size())?;
         let mut open = G1Element::zero();
         for j in 0..v.len() {
             if j != index {
                 let omega_i = self.domain.element(index);
                 let omega_j = self.domain.element(j);
 
-                let c_i = (omega_i - omega_j).inverse().unwrap();
-                let c_j = (omega_j - omega_i).inverse().unwrap();
+                let c_i = (omega_i - omega_j).inverse()?;
+                let c_j = (omega_j - omega_i).inverse()?;
 
                 let w_ij = self.a_vec[index].mul(c_i) + self.a_vec[j].mul(c_j);
 
-                let omega_j_n = (omega_j / Scalar::from(v.len() as u128)).unwrap();
+                let omega_j_n = (omega_j / Scalar::from(v.len() as u128))?;
                 let u_ij = w_ij.mul(omega_j_n);
 
                 open += u_ij.mul(v[j]);
             }
         }
         open += self.u_vec[index].mul(v[index]);
-        open
+        Ok(Opening::new(open, self.domain.size()))
     }
 
-    fn open_all(&self, v: &[Scalar]) -> Vec<G1Element> {
-        let domain = &self.domain;
+    fn open_all(&self, v: &[Scalar]) -> FastCryptoResult<Vec<Opening<Self>>> {
+        self.domain.install(|| {
+            let domain = &self.domain;
 
-        let poly = domain.ifft(&v);
-        let poly_degree = poly.len() - 1;
-        let mut t = self.tau_powers_g1.clone();
-        t.truncate(poly_degree);
+            check_length(v, domain.size())?;
+            let poly = domain.ifft(v);
+            let poly_degree = poly.len() - 1;
+            let mut t = self.tau_powers_g1.clone();
+            t.truncate(poly_degree);
 
-        let mut h = multiply_toeplitz_with_v(&poly, &t, domain.size());
-        domain.fft_in_place_group(&mut h);
+            let mut h = multiply_toeplitz_with_v(&poly, &t, domain.size())?;
+            domain.fft_in_place_group_parallel(&mut h);
 
-        h
+            Ok(h.into_iter()
+                .map(|open| Opening::new(open, domain.size()))
+                .collect())
+        })
     }
 
-    fn verify(
-        &self,
-        index: usize,
-        v_i: &Scalar,
-        commitment: &G1Element,
-        open_i: &G1Element,
-    ) -> bool {
-        let lhs = *commitment - G1Element::generator() * v_i;
-        let rhs = self.g2_tau - G2Element::generator() * self.domain.element(index);
-
-        lhs.pairing(&G2Element::generator()) == open_i.pairing(&rhs)
+    fn update_hints(&self) -> (&[G1Element], &[G1Element]) {
+        (&self.l_vec, &self.u_vec)
     }
 
-    fn update(
-        &self,
-        commitment: &mut G1Element,
-        index: usize,
-        old_v_i: &Scalar,
-        new_v_i: &Scalar,
-    ) -> G1Element {
-        *commitment + self.l_vec[index].mul(new_v_i - old_v_i)
+    fn open_subset(&self, v: &[Scalar], indices: &[usize]) -> FastCryptoResult<Opening<Self>> {
+        let quotient = subset_quotient(&self.domain, v, indices)?;
+        let open = G1Element::multi_scalar_mul(&quotient, &self.tau_powers_g1[..quotient.len()])?;
+        Ok(Opening::new(open, self.domain.size()))
     }
+}
 
-    fn update_open_i(
-        &self,
-        open: &G1Element,
-        index: usize,
-        old_v_i: &Scalar,
-        new_v_i: &Scalar,
-    ) -> G1Element {
-        *open + self.u_vec[index].mul(new_v_i - old_v_i)
+impl KeySerialization for KZGTabDFK {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = KeyWriter::new(KZG_TABDFK, self.domain.size());
+        writer.write_g2(&self.g2_tau);
+        writer.write_g1_vec(&self.tau_powers_g1);
+        writer.write_g1_vec(&self.l_vec);
+        writer.write_g1_vec(&self.u_vec);
+        writer.write_g1_vec(&self.a_vec);
+        writer.finish()
     }
 
-    fn update_open_j(
-        &self,
-        open: &G1Element,
-        index: usize,
-        index_j: usize,
-        old_v_j: &Scalar,
-        new_v_j: &Scalar,
-    ) -> G1Element {
-        let omega_i = self.domain.element(index);
-        let omega_j = self.domain.element(index_j);
-
-        let c_i = (omega_i - omega_j).inverse().unwrap();
-        let c_j = (omega_j - omega_i).inverse().unwrap();
-
-        let w_ij = self.a_vec[index].mul(c_i) + self.a_vec[index_j].mul(c_j);
-
-        let omega_j_n = (omega_j / Scalar::from(self.domain.size() as u128)).unwrap();
-        let u_ij = w_ij.mul(omega_j_n);
-        *open + u_ij.mul(new_v_j - old_v_j)
+    fn from_bytes(bytes: &[u8]) -> FastCryptoResult<Self> {
+        let mut reader = KeyReader::new(bytes, KZG_TABDFK)?;
+        let domain = reader.domain()?;
+        let g2_tau = reader.read_g2()?;
+        let tau_powers_g1 = reader.read_g1_vec()?;
+        let l_vec = reader.read_g1_vec()?;
+        let u_vec = reader.read_g1_vec()?;
+        let a_vec = reader.read_g1_vec()?;
+        reader.finish()?;
+
+        Ok(Self {
+            domain,
+            g2_tau,
+            u_vec,
+            l_vec,
+            a_vec,
+            tau_powers_g1,
+        })
     }
 }
 
@@ -236,6 +222,7 @@ mod tests {
     use rand::Rng;
 
     use super::*;
+    use crate::VerifierKey;
 
     #[test]
     fn test_kzg_commit_open_verify() {
@@ -243,10 +230,12 @@ mod tests {
         let n = 8;
         let kzg = KZGTabDFK::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let open_value = kzg.open(&v, index);
-        let is_valid = kzg.verify(index, &v[index], &commitment, &open_value);
+        let open_value = kzg.open(&v, index).unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &commitment, &open_value);
         assert!(is_valid, "Verification of the opening should succeed.");
     }
 
@@ -256,13 +245,19 @@ mod tests {
         let n = 8;
         let kzg = KZGTabDFK::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let mut commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let mut open_value = kzg.open(&v, index);
+        let open_value = kzg.open(&v, index).unwrap();
         let new_v_index = Scalar::rand(&mut rng);
-        let new_commitment = kzg.update(&mut commitment, index, &v[index], &new_v_index);
-        let new_opening = kzg.update_open_i(&mut open_value, index, &v[index], &new_v_index);
-        let is_valid = kzg.verify(index, &new_v_index, &new_commitment, &new_opening);
+        let new_commitment = kzg
+            .update(&commitment, index, &v[index], &new_v_index)
+            .unwrap();
+        let new_opening = kzg
+            .update_open_i(&open_value, index, &v[index], &new_v_index)
+            .unwrap();
+        let is_valid =
+            kzg.verifier_key()
+                .verify(index, &new_v_index, &new_commitment, &new_opening);
         assert!(
             is_valid,
             "Verification of the opening after updating should succeed."
@@ -275,9 +270,9 @@ mod tests {
         let n = 8;
         let kzg = KZGTabDFK::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let mut commitment = kzg.commit(&v);
+        let commitment = kzg.commit(&v).unwrap();
         let index = rng.gen_range(0..n);
-        let mut open_value = kzg.open(&v, index);
+        let open_value = kzg.open(&v, index).unwrap();
 
         let mut index_j;
         loop {
@@ -288,29 +283,58 @@ mod tests {
         }
 
         let new_v_index_j = Scalar::rand(&mut rng);
-        let new_commitment = kzg.update(&mut commitment, index_j, &v[index_j], &new_v_index_j);
-        let new_opening =
-            kzg.update_open_j(&mut open_value, index, index_j, &v[index_j], &new_v_index_j);
-        let is_valid = kzg.verify(index, &v[index], &new_commitment, &new_opening);
+        let new_commitment = kzg
+            .update(&commitment, index_j, &v[index_j], &new_v_index_j)
+            .unwrap();
+        let new_opening = kzg
+            .update_open_j(&open_value, index, index_j, &v[index_j], &new_v_index_j)
+            .unwrap();
+        let is_valid = kzg
+            .verifier_key()
+            .verify(index, &v[index], &new_commitment, &new_opening);
         assert!(
             is_valid,
             "Verification of the opening after updating j's value should succeed."
         );
     }
 
+    #[test]
+    fn test_kzg_save_load() {
+        let mut rng = rand::thread_rng();
+        let n = 8;
+        let kzg = KZGTabDFK::new(n).unwrap();
+        let bytes = kzg.to_bytes();
+
+        let path = std::env::temp_dir().join(format!("kzg_tabdfk_{}.key", rng.gen::<u64>()));
+        kzg.save(&path).unwrap();
+        let loaded = KZGTabDFK::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        assert_eq!(loaded.commit(&v).unwrap(), kzg.commit(&v).unwrap());
+        assert_eq!(loaded.open(&v, 3).unwrap(), kzg.open(&v, 3).unwrap());
+
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        assert!(KZGTabDFK::from_bytes(&corrupted).is_err());
+        assert!(KZGTabDFK::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(crate::kzg_deriv::KZGDeriv::from_bytes(&bytes).is_err());
+    }
+
     #[test]
     fn test_kzg_commit_open_all() {
         let mut rng = rand::thread_rng();
         let n = 8;
         let kzg = KZGTabDFK::new(n).unwrap();
         let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
-        let commitment = kzg.commit(&v);
-        let mut open_values = kzg.open_all(&v);
+        let commitment = kzg.commit(&v).unwrap();
+        let mut open_values = kzg.open_all(&v).unwrap();
 
         open_values.truncate(n);
 
         for (i, open_value) in open_values.iter().enumerate() {
-            let is_valid = kzg.verify(i, &v[i], &commitment, open_value);
+            let is_valid = kzg.verifier_key().verify(i, &v[i], &commitment, open_value);
             assert!(
                 is_valid,
                 "Verification of the opening should succeed for index {}",
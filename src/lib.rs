@@ -1,57 +1,711 @@
 // Declare the modules
 
-use fastcrypto::error::FastCryptoResult;
-use fastcrypto::groups::bls12381::Scalar;
-use fastcrypto::groups::GroupElement;
+use std::sync::Arc;
+
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{G1Element, Scalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Scalar as OtherScalar};
+use fastcrypto::traits::AllowedRng;
+use rayon::ThreadPool;
+
+use crate::commitment::{Commitment, Opening};
+use crate::fft::{BLS12381Domain, FFTDomain};
+use crate::polynomial::{
+    barycentric_weights, batch_inverse, evaluate_in_lagrange_form, Evaluation,
+};
+use crate::srs::SRS;
+use crate::transcript::Transcript;
 
 pub mod kzg_deriv;
 pub mod kzg_fk;
 pub mod kzg_original;
 pub mod kzg_tabdfk;
 
+pub mod ceremony;
+pub mod commitment;
 pub mod fft;
+mod polynomial;
+pub mod ptau;
+pub mod serialize;
+pub mod shplonk;
+pub mod srs;
+pub mod store;
+mod transcript;
+pub mod verifier_key;
 
-pub trait KZG: Sized + Clone {
-    type G: GroupElement;
+const OPEN_MANY_DOMAIN_SEPARATION_TAG: &[u8] = b"KZG-OPEN-MANY-V1";
 
-    fn new(n: usize) -> FastCryptoResult<Self>;
+/// The operations of a KZG scheme that need the prover's precomputed key.
+///
+/// Malformed requests give an error instead of a panic. An index outside the domain gives an
+/// [FastCryptoError::InvalidInput] error. A vector whose length is not the size of the domain, or a
+/// commitment or opening made for a domain of another size, gives an
+/// [FastCryptoError::InputLengthWrong] error. An index which must differ from another index but
+/// does not gives an [FastCryptoError::NotEnoughInputs] error.
+pub trait ProverKey: Sized {
+    type G: MultiScalarMul<ScalarType = Scalar>;
 
-    fn commit(&self, v: &[<Self::G as GroupElement>::ScalarType]) -> Self::G;
+    /// Get the domain over which vectors are committed to.
+    fn domain(&self) -> &BLS12381Domain;
 
-    fn open(&self, v: &[<Self::G as GroupElement>::ScalarType], index: usize) -> Self::G;
+    fn commit(&self, v: &[Scalar]) -> FastCryptoResult<Commitment<Self>>;
 
-    fn open_all(&self, v: &[Scalar]) -> Vec<Self::G>;
+    fn open(&self, v: &[Scalar], index: usize) -> FastCryptoResult<Opening<Self>>;
 
-    fn verify(
-        &self,
-        index: usize,
-        v_i: &<Self::G as GroupElement>::ScalarType,
-        commitment: &Self::G,
-        open_i: &Self::G,
-    ) -> bool;
+    fn open_all(&self, v: &[Scalar]) -> FastCryptoResult<Vec<Opening<Self>>>;
 
+    /// Get the commitments [L_i(tau)]_1 to the Lagrange basis of the domain and the hints
+    /// [(L_i(tau) - 1) / (tau - omega^i)]_1 of [SRS::lagrange_quotients_g1], from which
+    /// commitments and openings are updated.
+    fn update_hints(&self) -> (&[Self::G], &[Self::G]);
+
+    /// Updates a commitment after the value at index changed, by adding (new_v_i - old_v_i) L_i.
     fn update(
         &self,
-        commitment: &mut Self::G,
+        commitment: &Commitment<Self>,
         index: usize,
-        old_v_i: &<Self::G as GroupElement>::ScalarType,
-        new_v_i: &<Self::G as GroupElement>::ScalarType,
-    ) -> Self::G;
+        old_v_i: &Scalar,
+        new_v_i: &Scalar,
+    ) -> FastCryptoResult<Commitment<Self>> {
+        let n = self.domain().size();
+        check_index(index, n)?;
+        check_size(commitment.size(), n)?;
+        let (l_vec, _) = self.update_hints();
+        Ok(Commitment::new(
+            *commitment.element() + l_vec[index] * (new_v_i - old_v_i),
+            n,
+        ))
+    }
 
+    /// Updates the opening at index after the value at index changed, by adding
+    /// (new_v_i - old_v_i) u_i.
     fn update_open_i(
         &self,
-        open: &Self::G,
+        open: &Opening<Self>,
         index: usize,
-        old_v_i: &<Self::G as GroupElement>::ScalarType,
-        new_v_i: &<Self::G as GroupElement>::ScalarType,
-    ) -> Self::G;
+        old_v_i: &Scalar,
+        new_v_i: &Scalar,
+    ) -> FastCryptoResult<Opening<Self>> {
+        let n = self.domain().size();
+        check_index(index, n)?;
+        check_size(open.size(), n)?;
+        let (_, u_vec) = self.update_hints();
+        Ok(Opening::new(
+            *open.element() + u_vec[index] * (new_v_i - old_v_i),
+            n,
+        ))
+    }
 
+    /// Updates the opening at index after the value at index_j changed. The indices must differ;
+    /// use [ProverKey::update_open_i] when they are the same.
+    ///
+    /// Since L_j(omega^i) = 0 and L_k(X) (X - omega^k) = omega^k (X^n - 1) / n for all k, the
+    /// quotient L_j(X) / (X - omega^i) is (omega^j / omega^i L_i(X) - L_j(X)) / (omega^i - omega^j),
+    /// so a change d_j of the value adds d_j times its commitment, which only needs the basis.
     fn update_open_j(
         &self,
-        open: &Self::G,
+        open: &Opening<Self>,
         index: usize,
         index_j: usize,
-        old_v_j: &<Self::G as GroupElement>::ScalarType,
-        new_v_j: &<Self::G as GroupElement>::ScalarType,
-    ) -> Self::G;
+        old_v_j: &Scalar,
+        new_v_j: &Scalar,
+    ) -> FastCryptoResult<Opening<Self>> {
+        let n = self.domain().size();
+        check_distinct_indices(index, index_j, n)?;
+        check_size(open.size(), n)?;
+        let (l_vec, _) = self.update_hints();
+        let omega_j = self.domain().element(index_j);
+
+        let s_j = (new_v_j - old_v_j) * (self.domain().element(index) - omega_j).inverse()?;
+        let delta = Self::G::multi_scalar_mul(
+            &[s_j * omega_j * self.domain().inverse_element(index), -s_j],
+            &[l_vec[index], l_vec[index_j]],
+        )?;
+        Ok(Opening::new(*open.element() + delta, n))
+    }
+
+    /// Updates a commitment after the values at several distinct indices changed, given as
+    /// (index, old_v, new_v). This costs a single MSM over the changed indices.
+    fn update_batch(
+        &self,
+        commitment: &Commitment<Self>,
+        updates: &[(usize, Scalar, Scalar)],
+    ) -> FastCryptoResult<Commitment<Self>> {
+        let n = self.domain().size();
+        check_updates(updates, n)?;
+        check_size(commitment.size(), n)?;
+        if updates.is_empty() {
+            return Ok(*commitment);
+        }
+        let (l_vec, _) = self.update_hints();
+        let (scalars, points): (Vec<Scalar>, Vec<Self::G>) = updates
+            .iter()
+            .map(|(j, old_v_j, new_v_j)| (new_v_j - old_v_j, l_vec[*j]))
+            .unzip();
+        let delta = Self::G::multi_scalar_mul(&scalars, &points)?;
+        Ok(Commitment::new(*commitment.element() + delta, n))
+    }
+
+    /// Updates the opening at index after the values at several distinct indices changed, given
+    /// as (index_j, old_v_j, new_v_j). The changed indices may include index itself. This costs a
+    /// single MSM and a single inversion, since the deltas of [ProverKey::update_open_j] for all
+    /// j != index share the term in L_i.
+    fn update_open_batch(
+        &self,
+        open: &Opening<Self>,
+        index: usize,
+        updates: &[(usize, Scalar, Scalar)],
+    ) -> FastCryptoResult<Opening<Self>> {
+        let n = self.domain().size();
+        check_index(index, n)?;
+        check_updates(updates, n)?;
+        check_size(open.size(), n)?;
+        if updates.is_empty() {
+            return Ok(*open);
+        }
+        let (l_vec, u_vec) = self.update_hints();
+        let domain = self.domain();
+
+        let omega_i = domain.element(index);
+        let others: Vec<&(usize, Scalar, Scalar)> =
+            updates.iter().filter(|(j, _, _)| *j != index).collect();
+        let differences: Vec<Scalar> = others
+            .iter()
+            .map(|(j, _, _)| omega_i - domain.element(*j))
+            .collect();
+        let inverses = batch_inverse(&differences)?;
+
+        let mut scalars = vec![Scalar::zero()];
+        let mut points = vec![l_vec[index]];
+        for ((j, old_v_j, new_v_j), inverse) in others.into_iter().zip(inverses) {
+            let s_j = (new_v_j - old_v_j) * inverse;
+            scalars[0] += s_j * domain.element(*j) * domain.inverse_element(index);
+            scalars.push(-s_j);
+            points.push(l_vec[*j]);
+        }
+        if let Some((_, old_v_i, new_v_i)) = updates.iter().find(|(j, _, _)| *j == index) {
+            scalars.push(new_v_i - old_v_i);
+            points.push(u_vec[index]);
+        }
+        let delta = Self::G::multi_scalar_mul(&scalars, &points)?;
+        Ok(Opening::new(*open.element() + delta, n))
+    }
+
+    /// Opens a KZG commitment at a non-empty set of distinct indices with a single proof,
+    /// [(p(tau) - I(tau)) / Z_S(tau)]_1, where Z_S vanishes on the points of the indices and I
+    /// interpolates the values at them. It is verified with a
+    /// [crate::verifier_key::SubsetVerifierKey].
+    fn open_subset(&self, v: &[Scalar], indices: &[usize]) -> FastCryptoResult<Opening<Self>>;
+
+    /// Evaluates the polynomial interpolating v over the domain at an arbitrary point z, e.g. a
+    /// random challenge, and returns the value y together with a proof
+    /// [(p(tau) - y) / (tau - z)]_1 which is verified with [VerifierKey::verify_at].
+    ///
+    /// Outside the domain the quotient is computed in evaluation form and committed to like a
+    /// vector, and inside it the proof is the usual opening.
+    fn open_at(&self, v: &[Scalar], z: &Scalar) -> FastCryptoResult<(Scalar, Opening<Self>)> {
+        match evaluate_in_lagrange_form(self.domain(), v, z)? {
+            Evaluation::InDomain(index) => Ok((v[index], self.open(v, index)?)),
+            Evaluation::Outside(value, quotient) => {
+                let proof = self.commit(&quotient)?.into_element();
+                Ok((value, Opening::new(proof, self.domain().size())))
+            }
+        }
+    }
+
+    /// Opens the vectors v_1, ..., v_m with commitments C_1, ..., C_m at the same index with a
+    /// single proof, which is the opening of sum_k gamma^k v_k. The challenge gamma is derived by
+    /// hashing the commitments, the index and the values at it, and the proof is verified with
+    /// [crate::verifier_key::KZGVerifierKey::verify_many].
+    fn open_many(
+        &self,
+        commitments: &[Commitment<Self>],
+        vectors: &[Vec<Scalar>],
+        index: usize,
+    ) -> FastCryptoResult<Opening<Self>>
+    where
+        Self: ProverKey<G = G1Element>,
+    {
+        let n = self.domain().size();
+        check_index(index, n)?;
+        if commitments.len() != vectors.len() {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        for (commitment, v) in commitments.iter().zip(vectors) {
+            check_size(commitment.size(), n)?;
+            check_length(v, n)?;
+        }
+
+        let values: Vec<Scalar> = vectors.iter().map(|v| v[index]).collect();
+        let challenge = open_many_challenge(commitments, index, &values);
+        self.open(&combine_vectors(vectors, &challenge, n)?, index)
+    }
+
+    /// Aggregates openings (i, pi_i) at distinct indices, e.g. from [ProverKey::open_all], into a
+    /// single proof for all the indices without needing the committed vector. The result equals
+    /// the proof from [ProverKey::open_subset] and is verified with
+    /// [crate::verifier_key::SubsetVerifierKey].
+    ///
+    /// As in aSVC, if A_I vanishes on the points omega^i for i in I, the sum of pi_i / A_I'(omega^i)
+    /// is [(p(tau) - R(tau)) / A_I(tau)]_1 where R interpolates the opened values.
+    fn aggregate(&self, openings: &[(usize, Opening<Self>)]) -> FastCryptoResult<Opening<Self>> {
+        let n = self.domain().size();
+        let indices: Vec<usize> = openings.iter().map(|(index, _)| *index).collect();
+        check_subset(&indices, n)?;
+        for (_, open_i) in openings {
+            check_size(open_i.size(), n)?;
+        }
+
+        let points: Vec<Scalar> = indices.iter().map(|&i| self.domain().element(i)).collect();
+        let proofs: Vec<Self::G> = openings
+            .iter()
+            .map(|(_, open_i)| *open_i.element())
+            .collect();
+        let aggregate = Self::G::multi_scalar_mul(&barycentric_weights(&points)?, &proofs)?;
+        Ok(Opening::new(aggregate, n))
+    }
+}
+
+/// Verification of openings, which only needs a small key that can be shared with light clients.
+pub trait VerifierKey {
+    type G: GroupElement<ScalarType = Scalar>;
+
+    /// Verifies an opening made by any scheme K sharing this verifier key. Commitments and
+    /// openings made for a domain of another size are rejected.
+    fn verify<K: ProverKey<G = Self::G>>(
+        &self,
+        index: usize,
+        v_i: &Scalar,
+        commitment: &Commitment<K>,
+        open_i: &Opening<K>,
+    ) -> bool;
+
+    /// Verifies a proof from [ProverKey::open_at] that the committed polynomial takes the value y
+    /// at the point z.
+    fn verify_at<K: ProverKey<G = Self::G>>(
+        &self,
+        z: &Scalar,
+        y: &Scalar,
+        commitment: &Commitment<K>,
+        proof: &Opening<K>,
+    ) -> bool;
+
+    /// Verifies many openings (index, v_i, open_i) of the same commitment at once. The checks are
+    /// combined with random weights drawn from rng, so this costs two pairings in total instead of
+    /// two per opening. An empty batch is accepted.
+    fn verify_batch<K: ProverKey<G = Self::G>, R: AllowedRng>(
+        &self,
+        commitment: &Commitment<K>,
+        openings: &[(usize, Scalar, Opening<K>)],
+        rng: &mut R,
+    ) -> bool;
+
+    /// Verifies many openings (C_i, index_i, v_i, open_i) of different commitments at once, with
+    /// random weights drawn from rng. If the batch is rejected, the positions in entries of all
+    /// the invalid openings are returned.
+    fn verify_batch_multi<K: ProverKey<G = Self::G>, R: AllowedRng>(
+        &self,
+        entries: &[(Commitment<K>, usize, Scalar, Opening<K>)],
+        rng: &mut R,
+    ) -> Result<(), Vec<usize>>;
+}
+
+/// A KZG scheme, given by its prover key together with the matching verifier key.
+pub trait KZG: ProverKey + Sized + Clone {
+    type VerifierKey: VerifierKey<G = Self::G>;
+
+    fn new(n: usize) -> FastCryptoResult<Self>;
+
+    /// Create a new instance for a domain of n elements from a trusted setup. Only the points in
+    /// the SRS are used, so this never needs to know tau.
+    fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self>;
+
+    /// Get the verifier key for this setup.
+    fn verifier_key(&self) -> Self::VerifierKey;
+
+    /// Runs [ProverKey::open_all], including its group FFTs, in the given thread pool instead of
+    /// the global rayon pool.
+    fn with_thread_pool(self, thread_pool: Arc<ThreadPool>) -> Self;
+}
+
+/// Returns an [FastCryptoError::InvalidInput] error if the index is outside a domain of size n.
+pub(crate) fn check_index(index: usize, n: usize) -> FastCryptoResult<()> {
+    if index >= n {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Returns an error unless index and index_j are distinct indices in a domain of size n. Equal
+/// indices give an [FastCryptoError::NotEnoughInputs] error.
+pub(crate) fn check_distinct_indices(
+    index: usize,
+    index_j: usize,
+    n: usize,
+) -> FastCryptoResult<()> {
+    check_index(index, n)?;
+    check_index(index_j, n)?;
+    if index == index_j {
+        return Err(FastCryptoError::NotEnoughInputs);
+    }
+    Ok(())
+}
+
+/// Returns an error unless the indices are in a domain of size n. An empty set of indices or a
+/// repeated index gives an [FastCryptoError::NotEnoughInputs] error.
+pub(crate) fn check_subset(indices: &[usize], n: usize) -> FastCryptoResult<()> {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    check_index(*sorted.last().ok_or(FastCryptoError::NotEnoughInputs)?, n)?;
+    if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err(FastCryptoError::NotEnoughInputs);
+    }
+    Ok(())
+}
+
+/// Returns an error unless the indices of a batch of updates (index, old_v, new_v) are in the
+/// domain and distinct. An empty batch is allowed.
+pub(crate) fn check_updates(updates: &[(usize, Scalar, Scalar)], n: usize) -> FastCryptoResult<()> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    let indices: Vec<usize> = updates.iter().map(|(index, _, _)| *index).collect();
+    check_subset(&indices, n)
+}
+
+/// Returns an [FastCryptoError::InputLengthWrong] error if a commitment or opening was computed
+/// for a domain of another size than n.
+pub(crate) fn check_size(size: usize, n: usize) -> FastCryptoResult<()> {
+    if size != n {
+        return Err(FastCryptoError::InputLengthWrong(n));
+    }
+    Ok(())
+}
+
+/// Returns an [FastCryptoError::InputLengthWrong] error unless the vector has exactly one entry
+/// for each of the n elements of the domain.
+pub(crate) fn check_length(v: &[Scalar], n: usize) -> FastCryptoResult<()> {
+    check_size(v.len(), n)
+}
+
+/// Derives the challenge of [ProverKey::open_many] from the commitments, the index and the values
+/// of the committed vectors at the index.
+pub(crate) fn open_many_challenge<K: ProverKey<G = G1Element>>(
+    commitments: &[Commitment<K>],
+    index: usize,
+    values: &[Scalar],
+) -> Scalar {
+    let mut transcript = Transcript::new(OPEN_MANY_DOMAIN_SEPARATION_TAG);
+    transcript.append_usize(index);
+    transcript.append_usize(commitments.len());
+    for (commitment, v_k) in commitments.iter().zip(values) {
+        transcript.append_g1(commitment.element());
+        transcript.append_scalar(v_k);
+    }
+    transcript.challenge()
+}
+
+/// Returns sum_k challenge^k v_k for vectors v_k of length n. Since the polynomial interpolating a
+/// vector is linear in the vector, this is the vector of the same combination of the polynomials.
+pub(crate) fn combine_vectors(
+    vectors: &[Vec<Scalar>],
+    challenge: &Scalar,
+    n: usize,
+) -> FastCryptoResult<Vec<Scalar>> {
+    if vectors.is_empty() {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let mut combined = vec![Scalar::zero(); n];
+    for v in vectors.iter().rev() {
+        check_length(v, n)?;
+        for (c, v_i) in combined.iter_mut().zip(v) {
+            *c = *c * challenge + v_i;
+        }
+    }
+    Ok(combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::error::FastCryptoError;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+    use crate::kzg_fk::KZGFK;
+    use crate::kzg_original::KZGOriginal;
+    use crate::kzg_tabdfk::KZGTabDFK;
+    use crate::verifier_key::{KZGVerifierKey, SubsetVerifierKey};
+
+    fn check_invalid_inputs<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = K::new(n).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_value = kzg.open(&v, 2).unwrap();
+        let new_v = Scalar::rand(&mut rng);
+
+        let too_long = [v.as_slice(), &v[..1]].concat();
+        for wrong_length in [&v[..n - 1], too_long.as_slice()] {
+            let error = Some(FastCryptoError::InputLengthWrong(n));
+            assert_eq!(kzg.commit(wrong_length).err(), error);
+            assert_eq!(kzg.open(wrong_length, 2).err(), error);
+            assert_eq!(kzg.open_all(wrong_length).err(), error);
+        }
+        assert_eq!(
+            kzg.open_all(&[]).err(),
+            Some(FastCryptoError::InputLengthWrong(n))
+        );
+
+        let error = Some(FastCryptoError::InvalidInput);
+        assert_eq!(kzg.open(&v, n).err(), error);
+        assert_eq!(kzg.update(&commitment, n, &v[0], &new_v).err(), error);
+        assert_eq!(
+            kzg.update_open_i(&open_value, n, &v[0], &new_v).err(),
+            error
+        );
+        assert_eq!(
+            kzg.update_open_j(&open_value, 2, n, &v[0], &new_v).err(),
+            error
+        );
+
+        let error = Some(FastCryptoError::NotEnoughInputs);
+        assert_eq!(
+            kzg.update_open_j(&open_value, 2, 2, &v[2], &new_v).err(),
+            error
+        );
+        assert_eq!(kzg.open_subset(&v, &[1, 1]).err(), error);
+        assert_eq!(kzg.open_subset(&v, &[]).err(), error);
+
+        let resized = Opening::new(*open_value.element(), 2 * n);
+        assert_eq!(
+            kzg.update_open_i(&resized, 2, &v[2], &new_v),
+            Err(FastCryptoError::InputLengthWrong(n))
+        );
+    }
+
+    fn check_from_srs<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let tau = Scalar::rand(&mut rng);
+        let kzg = K::from_srs(n, &SRS::from_tau(n, &tau)).unwrap();
+        let vk = kzg.verifier_key();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_values = kzg.open_all(&v).unwrap();
+        for (i, open_value) in open_values.iter().enumerate() {
+            assert!(vk.verify(i, &v[i], &commitment, open_value));
+        }
+
+        let new_v_3 = Scalar::rand(&mut rng);
+        let new_commitment = kzg.update(&commitment, 3, &v[3], &new_v_3).unwrap();
+        let new_opening = kzg
+            .update_open_j(&open_values[1], 1, 3, &v[3], &new_v_3)
+            .unwrap();
+        assert!(vk.verify(1, &v[1], &new_commitment, &new_opening));
+        let new_opening = kzg
+            .update_open_i(&open_values[3], 3, &v[3], &new_v_3)
+            .unwrap();
+        assert!(vk.verify(3, &new_v_3, &new_commitment, &new_opening));
+
+        assert!(K::from_srs(n, &SRS::from_tau(n - 1, &tau)).is_err());
+    }
+
+    fn check_commit_open_update<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = K::new(n).unwrap();
+        let mut v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let (index, index_j) = (2, 5);
+        let open_i = kzg.open(&v, index).unwrap();
+        let open_j = kzg.open(&v, index_j).unwrap();
+
+        // Update the value at index j, which changes both openings.
+        let new_v_j = Scalar::rand(&mut rng);
+        let commitment = kzg
+            .update(&commitment, index_j, &v[index_j], &new_v_j)
+            .unwrap();
+        let open_i = kzg
+            .update_open_j(&open_i, index, index_j, &v[index_j], &new_v_j)
+            .unwrap();
+        let open_j = kzg
+            .update_open_i(&open_j, index_j, &v[index_j], &new_v_j)
+            .unwrap();
+        v[index_j] = new_v_j;
+
+        assert_eq!(commitment, kzg.commit(&v).unwrap());
+        assert_eq!(open_i, kzg.open(&v, index).unwrap());
+        assert_eq!(open_j, kzg.open(&v, index_j).unwrap());
+        let vk = kzg.verifier_key();
+        assert!(vk.verify(index, &v[index], &commitment, &open_i));
+        assert!(vk.verify(index_j, &v[index_j], &commitment, &open_j));
+    }
+
+    fn check_update_batch<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = K::new(n).unwrap();
+        let mut v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_values = kzg.open_all(&v).unwrap();
+
+        let updates: Vec<(usize, Scalar, Scalar)> = [6, 1, 3]
+            .iter()
+            .map(|&j| (j, v[j], Scalar::rand(&mut rng)))
+            .collect();
+        let new_commitment = kzg.update_batch(&commitment, &updates).unwrap();
+        let new_openings: Vec<Opening<K>> = (0..n)
+            .map(|i| kzg.update_open_batch(&open_values[i], i, &updates).unwrap())
+            .collect();
+        for (j, _, new_v_j) in &updates {
+            v[*j] = *new_v_j;
+        }
+        assert_eq!(new_commitment, kzg.commit(&v).unwrap());
+        assert_eq!(new_openings, kzg.open_all(&v).unwrap());
+
+        assert_eq!(kzg.update_batch(&commitment, &[]).unwrap(), commitment);
+        assert_eq!(
+            kzg.update_open_batch(&open_values[0], 0, &[]).unwrap(),
+            open_values[0]
+        );
+        assert!(kzg
+            .update_batch(&commitment, &[updates[0], updates[0]])
+            .is_err());
+        assert!(kzg
+            .update_open_batch(&open_values[0], 0, &[(n, v[0], v[1])])
+            .is_err());
+    }
+
+    fn check_aggregate<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let srs = SRS::from_tau_with_g2_powers(n, 4, &Scalar::rand(&mut rng));
+        let kzg = K::from_srs(n, &srs).unwrap();
+        let vk = SubsetVerifierKey::from_srs(n, &srs, 3).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_values = kzg.open_all(&v).unwrap();
+
+        let indices = [6, 1, 4];
+        let openings: Vec<(usize, Opening<K>)> =
+            indices.iter().map(|&i| (i, open_values[i])).collect();
+        let values: Vec<(usize, Scalar)> = indices.iter().map(|&i| (i, v[i])).collect();
+        let aggregate = kzg.aggregate(&openings).unwrap();
+        assert_eq!(aggregate, kzg.open_subset(&v, &indices).unwrap());
+        assert!(vk.verify_aggregate(&values, &commitment, &aggregate));
+        assert!(!vk.verify_aggregate(&values[..2], &commitment, &aggregate));
+
+        assert!(kzg.aggregate(&[]).is_err());
+        assert!(kzg.aggregate(&[openings[0], openings[0]]).is_err());
+    }
+
+    fn check_open_many<K: KZG<G = G1Element, VerifierKey = KZGVerifierKey>>() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = K::new(n).unwrap();
+        let vk = kzg.verifier_key();
+        let vectors: Vec<Vec<Scalar>> = (0..3)
+            .map(|_| (0..n).map(|_| OtherScalar::rand(&mut rng)).collect())
+            .collect();
+        let commitments: Vec<Commitment<K>> =
+            vectors.iter().map(|v| kzg.commit(v).unwrap()).collect();
+        let index = 5;
+        let values: Vec<Scalar> = vectors.iter().map(|v| v[index]).collect();
+
+        let proof = kzg.open_many(&commitments, &vectors, index).unwrap();
+        assert!(vk.verify_many(index, &values, &commitments, &proof));
+        assert!(!vk.verify_many(index, &values[..2], &commitments[..2], &proof));
+        let mut wrong_values = values.clone();
+        wrong_values[1] += Scalar::generator();
+        assert!(!vk.verify_many(index, &wrong_values, &commitments, &proof));
+        let mut swapped_values = values.clone();
+        let mut swapped_commitments = commitments.clone();
+        swapped_values.swap(0, 2);
+        swapped_commitments.swap(0, 2);
+        assert!(!vk.verify_many(index, &swapped_values, &swapped_commitments, &proof));
+
+        // A single vector is opened as it is.
+        let proof = kzg
+            .open_many(&commitments[..1], &vectors[..1], index)
+            .unwrap();
+        assert_eq!(proof, kzg.open(&vectors[0], index).unwrap());
+
+        assert!(kzg.open_many(&[], &[], index).is_err());
+        assert!(kzg.open_many(&commitments[..2], &vectors, index).is_err());
+        assert!(kzg.open_many(&commitments, &vectors, n).is_err());
+    }
+
+    #[test]
+    fn test_from_srs() {
+        check_from_srs::<KZGOriginal>();
+        check_from_srs::<KZGFK>();
+        check_from_srs::<KZGTabDFK>();
+        check_from_srs::<KZGDeriv>();
+    }
+
+    #[test]
+    fn test_commit_open_update() {
+        check_commit_open_update::<KZGOriginal>();
+        check_commit_open_update::<KZGFK>();
+        check_commit_open_update::<KZGTabDFK>();
+        check_commit_open_update::<KZGDeriv>();
+    }
+
+    #[test]
+    fn test_update_batch() {
+        check_update_batch::<KZGOriginal>();
+        check_update_batch::<KZGFK>();
+        check_update_batch::<KZGTabDFK>();
+        check_update_batch::<KZGDeriv>();
+    }
+
+    fn check_open_all_with_thread_pool<K: KZG<G = G1Element>>() {
+        let mut rng = thread_rng();
+        let n = 16;
+        let kzg = K::new(n).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let kzg_with_pool = kzg.clone().with_thread_pool(Arc::new(thread_pool));
+        assert_eq!(
+            kzg_with_pool.open_all(&v).unwrap(),
+            kzg.open_all(&v).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        check_aggregate::<KZGOriginal>();
+        check_aggregate::<KZGFK>();
+        check_aggregate::<KZGTabDFK>();
+        check_aggregate::<KZGDeriv>();
+    }
+
+    #[test]
+    fn test_open_many() {
+        check_open_many::<KZGOriginal>();
+        check_open_many::<KZGFK>();
+        check_open_many::<KZGTabDFK>();
+        check_open_many::<KZGDeriv>();
+    }
+
+    #[test]
+    fn test_open_all_with_thread_pool() {
+        check_open_all_with_thread_pool::<KZGOriginal>();
+        check_open_all_with_thread_pool::<KZGFK>();
+        check_open_all_with_thread_pool::<KZGTabDFK>();
+        check_open_all_with_thread_pool::<KZGDeriv>();
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        check_invalid_inputs::<KZGOriginal>();
+        check_invalid_inputs::<KZGFK>();
+        check_invalid_inputs::<KZGTabDFK>();
+        check_invalid_inputs::<KZGDeriv>();
+    }
 }
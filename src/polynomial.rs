@@ -0,0 +1,284 @@
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::Scalar;
+use fastcrypto::groups::{GroupElement, Scalar as OtherScalar};
+
+use crate::fft::{BLS12381Domain, FFTDomain};
+use crate::{check_length, check_subset};
+
+/// Performs polynomial division of the dividend by the divisor
+/// Returns the quotient and remainder
+pub(crate) fn polynomial_division(
+    dividend: &[Scalar],
+    divisor: &[Scalar],
+) -> FastCryptoResult<(Vec<Scalar>, Vec<Scalar>)> {
+    let mut remainder = Vec::from(dividend);
+
+    let divisor_leading_term_inverse = divisor
+        .last()
+        .ok_or(FastCryptoError::InvalidInput)?
+        .inverse()?;
+
+    let quotient_size = (dividend.len() + 1).saturating_sub(divisor.len());
+
+    let mut quotient: Vec<Scalar> = (0..quotient_size)
+        .rev()
+        .map(|i| {
+            let q_i = remainder[i + divisor.len() - 1] * divisor_leading_term_inverse;
+            for j in 0..divisor.len() {
+                remainder[i + j] -= q_i * divisor[j];
+            }
+            q_i
+        })
+        .collect();
+    quotient.reverse();
+
+    // Remove leading zeros in the remainder
+    while remainder.len() > 1 && remainder.last().unwrap() == &Scalar::zero() {
+        remainder.pop();
+    }
+
+    Ok((quotient, remainder))
+}
+
+/// Evaluates the polynomial with the given coefficients at x using Horner's rule.
+pub(crate) fn evaluate(coefficients: &[Scalar], x: &Scalar) -> Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::zero(), |acc, c| acc * x + c)
+}
+
+/// Returns the coefficients of the polynomial prod_i (X - x_i) which vanishes on the given points.
+pub(crate) fn vanishing_polynomial(points: &[Scalar]) -> Vec<Scalar> {
+    let mut result = vec![Scalar::generator()];
+    for x_i in points {
+        // Multiply by X - x_i.
+        result.insert(0, Scalar::zero());
+        for j in 0..result.len() - 1 {
+            let t = result[j + 1] * x_i;
+            result[j] -= t;
+        }
+    }
+    result
+}
+
+/// Returns the barycentric weights 1 / prod_{j != i} (x_i - x_j) of distinct points x_i, which
+/// are the inverses of Z'(x_i) for the polynomial Z vanishing on all the points.
+pub(crate) fn barycentric_weights(points: &[Scalar]) -> FastCryptoResult<Vec<Scalar>> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Scalar::generator(), |acc, (_, x_j)| acc * (x_i - x_j))
+                .inverse()
+        })
+        .collect()
+}
+
+/// Returns the coefficients of the polynomial of degree less than the number of points which takes
+/// the given values at the given points, which must be distinct.
+///
+/// This is computed as sum_i v_i w_i Z(X) / (X - x_i) where Z vanishes on all the points and w_i
+/// are the barycentric weights, using O(k^2) operations for k points.
+pub(crate) fn interpolate(points: &[Scalar], values: &[Scalar]) -> FastCryptoResult<Vec<Scalar>> {
+    if points.len() != values.len() {
+        return Err(FastCryptoError::InputLengthWrong(points.len()));
+    }
+    let z = vanishing_polynomial(points);
+    let weights = barycentric_weights(points)?;
+    let mut result = vec![Scalar::zero(); points.len()];
+    for ((x_i, v_i), w_i) in points.iter().zip(values).zip(weights) {
+        let (z_i, _) = polynomial_division(&z, &[-*x_i, Scalar::generator()])?;
+        let c_i = *v_i * w_i;
+        for (r, z_ij) in result.iter_mut().zip(&z_i) {
+            *r += c_i * z_ij;
+        }
+    }
+    Ok(result)
+}
+
+/// Computes x^exponent by repeated squaring.
+pub(crate) fn pow(x: &Scalar, exponent: usize) -> Scalar {
+    let mut result = Scalar::generator();
+    let mut power = *x;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result *= power;
+        }
+        power = power * power;
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Inverts all the elements with a single field inversion using Montgomery's trick. Fails if any
+/// of the elements is zero.
+pub(crate) fn batch_inverse(v: &[Scalar]) -> FastCryptoResult<Vec<Scalar>> {
+    // prefix_products[i] is the product of the first i elements.
+    let mut prefix_products = Vec::with_capacity(v.len());
+    let mut product = Scalar::generator();
+    for x in v {
+        prefix_products.push(product);
+        product *= x;
+    }
+
+    let mut inverse = product.inverse()?;
+    let mut result = vec![Scalar::zero(); v.len()];
+    for i in (0..v.len()).rev() {
+        result[i] = inverse * prefix_products[i];
+        inverse *= v[i];
+    }
+    Ok(result)
+}
+
+/// The evaluation at a point z of the polynomial p interpolating a vector over the domain.
+pub(crate) enum Evaluation {
+    /// The point is omega^index in the domain, so the value is the entry at that index.
+    InDomain(usize),
+    /// The point is outside the domain. Holds p(z) and the evaluations over the domain of the
+    /// quotient (p(X) - p(z)) / (X - z).
+    Outside(Scalar, Vec<Scalar>),
+}
+
+/// Evaluates the polynomial interpolating v over the domain at z. Outside the domain the value is
+/// given by the barycentric formula p(z) = (z^n - 1) / n * sum_i v_i omega^i / (z - omega^i), and
+/// the quotient by (p(X) - p(z)) / (X - z) at omega^i is (v_i - p(z)) / (omega^i - z). This takes
+/// O(n) operations and a single inversion.
+pub(crate) fn evaluate_in_lagrange_form(
+    domain: &BLS12381Domain,
+    v: &[Scalar],
+    z: &Scalar,
+) -> FastCryptoResult<Evaluation> {
+    let n = domain.size();
+    check_length(v, n)?;
+    let omega_powers = domain.elements();
+
+    let differences: Vec<Scalar> = omega_powers.iter().map(|omega_i| z - omega_i).collect();
+    if let Some(index) = differences.iter().position(|d| d == &Scalar::zero()) {
+        return Ok(Evaluation::InDomain(index));
+    }
+    let inverses = batch_inverse(&differences)?;
+
+    let sum = v
+        .iter()
+        .zip(omega_powers)
+        .zip(&inverses)
+        .fold(Scalar::zero(), |sum, ((v_i, omega_i), inverse)| {
+            sum + v_i * omega_i * inverse
+        });
+    let value = (pow(z, n) - Scalar::generator()) * domain.size_inv() * sum;
+
+    let quotient = v
+        .iter()
+        .zip(&inverses)
+        .map(|(v_i, inverse)| (value - v_i) * inverse)
+        .collect();
+    Ok(Evaluation::Outside(value, quotient))
+}
+
+/// Returns the coefficients of the quotient (p(X) - I(X)) / Z_S(X), where p interpolates v over
+/// the domain, Z_S vanishes on the points omega^i for i in indices and I interpolates v on them.
+/// The quotient is computed by dividing p by Z_S, whose remainder is exactly I.
+pub(crate) fn subset_quotient(
+    domain: &BLS12381Domain,
+    v: &[Scalar],
+    indices: &[usize],
+) -> FastCryptoResult<Vec<Scalar>> {
+    check_subset(indices, domain.size())?;
+    check_length(v, domain.size())?;
+    let poly = domain.ifft(v);
+    let points: Vec<Scalar> = indices.iter().map(|&i| domain.element(i)).collect();
+    let (mut quotient, _) = polynomial_division(&poly, &vanishing_polynomial(&points))?;
+    if quotient.is_empty() {
+        // The subset is the whole domain, so p = I and the quotient is zero.
+        quotient.push(Scalar::zero());
+    }
+    Ok(quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_batch_inverse_and_pow() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..5).map(|_| Scalar::rand(&mut rng)).collect();
+        let inverses = batch_inverse(&v).unwrap();
+        for (x, inverse) in v.iter().zip(&inverses) {
+            assert_eq!(x * inverse, Scalar::generator());
+            assert_eq!(pow(x, 5), x * x * x * x * x);
+        }
+        assert_eq!(pow(&v[0], 0), Scalar::generator());
+        assert!(batch_inverse(&[v[0], Scalar::zero()]).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_in_lagrange_form() {
+        let mut rng = thread_rng();
+        let domain = BLS12381Domain::new(8).unwrap();
+        let v: Vec<Scalar> = (0..8).map(|_| Scalar::rand(&mut rng)).collect();
+        let poly = domain.ifft(&v);
+
+        let z = Scalar::rand(&mut rng);
+        let Evaluation::Outside(value, quotient) =
+            evaluate_in_lagrange_form(&domain, &v, &z).unwrap()
+        else {
+            panic!("z should be outside the domain");
+        };
+        assert_eq!(value, evaluate(&poly, &z));
+        let x = Scalar::rand(&mut rng);
+        assert_eq!(
+            evaluate(&domain.ifft(&quotient), &x) * (x - z),
+            evaluate(&poly, &x) - value
+        );
+
+        assert!(matches!(
+            evaluate_in_lagrange_form(&domain, &v, &domain.element(3)),
+            Ok(Evaluation::InDomain(3))
+        ));
+    }
+
+    #[test]
+    fn test_interpolate_and_divide() {
+        let mut rng = thread_rng();
+        let points: Vec<Scalar> = (0..5).map(|_| Scalar::rand(&mut rng)).collect();
+        let values: Vec<Scalar> = (0..5).map(|_| Scalar::rand(&mut rng)).collect();
+
+        let z = vanishing_polynomial(&points);
+        assert_eq!(z.len(), 6);
+        let i = interpolate(&points, &values).unwrap();
+        for (x, v) in points.iter().zip(&values) {
+            assert_eq!(evaluate(&z, x), Scalar::zero());
+            assert_eq!(evaluate(&i, x), *v);
+        }
+
+        // Dividing q * z + i by z gives back q and i.
+        let q: Vec<Scalar> = (0..4).map(|_| Scalar::rand(&mut rng)).collect();
+        let x = Scalar::rand(&mut rng);
+        let mut dividend = vec![Scalar::zero(); q.len() + z.len() - 1];
+        for (j, q_j) in q.iter().enumerate() {
+            for (k, z_k) in z.iter().enumerate() {
+                dividend[j + k] += q_j * z_k;
+            }
+        }
+        for (d, i_j) in dividend.iter_mut().zip(&i) {
+            *d += i_j;
+        }
+        let (quotient, remainder) = polynomial_division(&dividend, &z).unwrap();
+        assert_eq!(quotient, q);
+        assert_eq!(evaluate(&remainder, &x), evaluate(&i, &x));
+
+        // A dividend of lower degree than the divisor is its own remainder.
+        let (quotient, remainder) = polynomial_division(&i, &z).unwrap();
+        assert_eq!(quotient, Vec::<Scalar>::new());
+        assert_eq!(evaluate(&remainder, &x), evaluate(&i, &x));
+    }
+}
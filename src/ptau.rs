@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use ark_bls12_381::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInt, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{G1Element, G2Element};
+use fastcrypto::serde_helpers::ToFromByteArray;
+
+use crate::fft::{BLS12381Domain, FFTDomain};
+use crate::srs::SRS;
+
+/// Byte length of a base field element in a BLS12-381 `.ptau` file.
+const N8: usize = 48;
+
+const HEADER_SECTION: u32 = 1;
+const TAU_G1_SECTION: u32 = 2;
+const TAU_G2_SECTION: u32 = 3;
+
+/// Reads the powers of tau from a snarkjs `.ptau` file, as produced by the Perpetual Powers of Tau
+/// and Hermez ceremonies, and returns an SRS with the first powers in G1 needed for a domain of n
+/// elements together with [tau]_2.
+///
+/// The file consists of the magic string "ptau", a version and a list of sections, each given by
+/// its type and byte length. The header section holds the size of the base field elements, the
+/// field modulus and the power of the ceremony, and the tauG1 and tauG2 sections hold the powers
+/// of tau as uncompressed points with coordinates in little-endian Montgomery form. Only the
+/// sections that are needed are read, so large transcripts can be used directly.
+///
+/// Only transcripts over BLS12-381 are accepted; every point is checked to be on the curve and in
+/// the prime order subgroup.
+pub fn read_srs<R: Read + Seek>(reader: &mut R, n: usize) -> FastCryptoResult<SRS> {
+    read_srs_with_g2_powers(reader, n, 2)
+}
+
+/// Like [read_srs] but also reads the first n_g2 powers of tau in G2, which are needed to verify
+/// openings of subsets of up to n_g2 - 1 indices.
+pub fn read_srs_with_g2_powers<R: Read + Seek>(
+    reader: &mut R,
+    n: usize,
+    n_g2: usize,
+) -> FastCryptoResult<SRS> {
+    let n_dom = BLS12381Domain::new(n)?.size();
+    if n_g2 < 2 {
+        return Err(FastCryptoError::InvalidInput);
+    }
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(io_error)?;
+    if &magic != b"ptau" {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let _version = read_u32(reader)?;
+    let n_sections = read_u32(reader)?;
+
+    // Map from section type to the offset and length of its data.
+    let mut sections = HashMap::new();
+    for _ in 0..n_sections {
+        let section_type = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let offset = reader.stream_position().map_err(io_error)?;
+        sections.insert(section_type, (offset, size));
+        reader
+            .seek(SeekFrom::Current(size as i64))
+            .map_err(io_error)?;
+    }
+
+    seek_section(reader, &sections, HEADER_SECTION)?;
+    if read_u32(reader)? as usize != N8 {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    if read_fq_bigint(reader)? != Fq::MODULUS {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let power = read_u32(reader)?;
+    if power >= usize::BITS || 1usize << power < n_dom {
+        return Err(FastCryptoError::InputTooShort(n_dom));
+    }
+
+    if seek_section(reader, &sections, TAU_G1_SECTION)? < (n_dom * 2 * N8) as u64 {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let tau_powers_g1 = (0..n_dom)
+        .map(|_| read_g1(reader))
+        .collect::<FastCryptoResult<Vec<_>>>()?;
+
+    if seek_section(reader, &sections, TAU_G2_SECTION)? < (n_g2 * 4 * N8) as u64 {
+        return Err(FastCryptoError::InputTooShort(n_g2));
+    }
+    let tau_powers_g2 = (0..n_g2)
+        .map(|_| read_g2(reader))
+        .collect::<FastCryptoResult<Vec<_>>>()?;
+
+    SRS::with_g2_powers(tau_powers_g1, tau_powers_g2)
+}
+
+/// Moves the reader to the start of the data of the given section and returns its byte length.
+fn seek_section<R: Seek>(
+    reader: &mut R,
+    sections: &HashMap<u32, (u64, u64)>,
+    section_type: u32,
+) -> FastCryptoResult<u64> {
+    let (offset, size) = sections
+        .get(&section_type)
+        .ok_or(FastCryptoError::InvalidInput)?;
+    reader.seek(SeekFrom::Start(*offset)).map_err(io_error)?;
+    Ok(*size)
+}
+
+fn io_error(_: std::io::Error) -> FastCryptoError {
+    FastCryptoError::InvalidInput
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> FastCryptoResult<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes).map_err(io_error)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> FastCryptoResult<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes).map_err(io_error)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads N8 bytes as a little-endian integer.
+fn read_fq_bigint<R: Read>(reader: &mut R) -> FastCryptoResult<BigInt<6>> {
+    let mut limbs = [0u64; 6];
+    for limb in limbs.iter_mut() {
+        *limb = read_u64(reader)?;
+    }
+    Ok(BigInt::new(limbs))
+}
+
+/// Reads a base field element in Montgomery form, which is also the internal representation used
+/// by arkworks.
+fn read_fq<R: Read>(reader: &mut R) -> FastCryptoResult<Fq> {
+    let bigint = read_fq_bigint(reader)?;
+    if bigint >= Fq::MODULUS {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    Ok(Fq::new_unchecked(bigint))
+}
+
+fn read_fq2<R: Read>(reader: &mut R) -> FastCryptoResult<Fq2> {
+    let c0 = read_fq(reader)?;
+    let c1 = read_fq(reader)?;
+    Ok(Fq2::new(c0, c1))
+}
+
+fn read_g1<R: Read>(reader: &mut R) -> FastCryptoResult<G1Element> {
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let mut bytes = [0u8; 48];
+    point
+        .serialize_compressed(&mut bytes[..])
+        .map_err(|_| FastCryptoError::InvalidInput)?;
+    // Deserialization checks that the point is in the prime order subgroup.
+    G1Element::from_byte_array(&bytes)
+}
+
+fn read_g2<R: Read>(reader: &mut R) -> FastCryptoResult<G2Element> {
+    let x = read_fq2(reader)?;
+    let y = read_fq2(reader)?;
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(FastCryptoError::InvalidInput);
+    }
+    let mut bytes = [0u8; 96];
+    point
+        .serialize_compressed(&mut bytes[..])
+        .map_err(|_| FastCryptoError::InvalidInput)?;
+    // Deserialization checks that the point is in the prime order subgroup.
+    G2Element::from_byte_array(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ark_serialize::CanonicalDeserialize;
+    use fastcrypto::groups::bls12381::Scalar;
+    use fastcrypto::groups::{GroupElement, Scalar as OtherScalar};
+    use rand::thread_rng;
+
+    use super::*;
+
+    fn write_fq(out: &mut Vec<u8>, f: &Fq) {
+        // The internal representation of an arkworks field element is its Montgomery form.
+        for limb in f.0 .0.iter() {
+            out.extend_from_slice(&limb.to_le_bytes());
+        }
+    }
+
+    fn write_section(out: &mut Vec<u8>, section_type: u32, data: &[u8]) {
+        out.extend_from_slice(&section_type.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+
+    /// Writes a .ptau file for a known tau with 2^power powers.
+    fn write_ptau(power: u32, tau: &Scalar) -> Vec<u8> {
+        let n = 1usize << power;
+        let g1_powers = itertools::iterate(G1Element::generator(), |g| g * tau).take(2 * n - 1);
+        let g2_powers = itertools::iterate(G2Element::generator(), |g| g * tau).take(n);
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(N8 as u32).to_le_bytes());
+        for limb in Fq::MODULUS.0.iter() {
+            header.extend_from_slice(&limb.to_le_bytes());
+        }
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+
+        let mut tau_g1 = Vec::new();
+        for p in g1_powers {
+            let affine = G1Affine::deserialize_compressed(&p.to_byte_array()[..]).unwrap();
+            write_fq(&mut tau_g1, &affine.x);
+            write_fq(&mut tau_g1, &affine.y);
+        }
+
+        let mut tau_g2 = Vec::new();
+        for p in g2_powers {
+            let affine = G2Affine::deserialize_compressed(&p.to_byte_array()[..]).unwrap();
+            for c in [affine.x.c0, affine.x.c1, affine.y.c0, affine.y.c1] {
+                write_fq(&mut tau_g2, &c);
+            }
+        }
+
+        let mut out = b"ptau".to_vec();
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&4u32.to_le_bytes());
+        write_section(&mut out, HEADER_SECTION, &header);
+        // Sections may appear in any order and unknown sections are skipped.
+        write_section(&mut out, TAU_G2_SECTION, &tau_g2);
+        write_section(&mut out, 7, &[0u8; 5]);
+        write_section(&mut out, TAU_G1_SECTION, &tau_g1);
+        out
+    }
+
+    #[test]
+    fn test_read_srs() {
+        let tau = Scalar::rand(&mut thread_rng());
+        let ptau = write_ptau(3, &tau);
+
+        let srs = read_srs(&mut Cursor::new(&ptau), 4).unwrap();
+        let expected = SRS::from_tau(4, &tau);
+        assert_eq!(srs.tau_powers_g1(), expected.tau_powers_g1());
+        assert_eq!(srs.g2_tau(), expected.g2_tau());
+
+        // The domain size is rounded up to the next power of two.
+        let srs = read_srs(&mut Cursor::new(&ptau), 7).unwrap();
+        assert_eq!(srs.tau_powers_g1(), SRS::from_tau(8, &tau).tau_powers_g1());
+
+        assert!(read_srs(&mut Cursor::new(&ptau), 9).is_err());
+
+        let srs = read_srs_with_g2_powers(&mut Cursor::new(&ptau), 4, 8).unwrap();
+        let expected = SRS::from_tau_with_g2_powers(4, 8, &tau);
+        assert_eq!(srs.tau_powers_g2(), expected.tau_powers_g2());
+        assert!(read_srs_with_g2_powers(&mut Cursor::new(&ptau), 4, 9).is_err());
+    }
+
+    #[test]
+    fn test_read_srs_rejects_invalid_points() {
+        let tau = Scalar::rand(&mut thread_rng());
+        let ptau = write_ptau(2, &tau);
+
+        // The tauG1 section is the last one and holds 7 points, of which only the first 4 are read.
+        let tau_g1_start = ptau.len() - 7 * 2 * N8;
+        let mut corrupted = ptau.clone();
+        corrupted[tau_g1_start + 6 * 2 * N8 + N8] ^= 1;
+        assert!(read_srs(&mut Cursor::new(&corrupted), 4).is_ok());
+
+        // Changing the y-coordinate of a point that is read moves it off the curve.
+        let mut corrupted = ptau.clone();
+        corrupted[tau_g1_start + 3 * 2 * N8 + N8] ^= 1;
+        assert!(read_srs(&mut Cursor::new(&corrupted), 4).is_err());
+
+        let mut corrupted = ptau;
+        corrupted[0] = b'x';
+        assert!(read_srs(&mut Cursor::new(&corrupted), 4).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{
+    G1Element, G2Element, G1_ELEMENT_BYTE_LENGTH, G2_ELEMENT_BYTE_LENGTH,
+};
+use fastcrypto::groups::FromTrustedByteArray;
+use fastcrypto::hash::{HashFunction, Sha256};
+use fastcrypto::serde_helpers::ToFromByteArray;
+
+use crate::fft::{BLS12381Domain, FFTDomain};
+
+const MAGIC: &[u8; 4] = b"KZGK";
+const VERSION: u8 = 1;
+const DIGEST_LENGTH: usize = 32;
+
+/// Scheme tags used in the header of a serialized key.
+pub(crate) const KZG_TABDFK: u8 = 1;
+pub(crate) const KZG_DERIV: u8 = 2;
+pub(crate) const KZG_VERIFIER: u8 = 3;
+
+/// Saving and loading of fully precomputed keys, so the precomputation only has to be done once.
+///
+/// A key is serialized as the magic string "KZGK", a version byte, a byte identifying the scheme
+/// and the domain size as a little-endian u64, followed by the group elements of the key in
+/// compressed form. Vectors are prefixed by their length. The serialization ends with the SHA-256
+/// digest of everything before it, which is checked when loading.
+///
+/// Since the digest only protects against corruption, keys should only be loaded from trusted
+/// storage: G1 points are checked to be on the curve but, for speed, not to be in the prime order
+/// subgroup. The single G2 point is fully checked, but that does not make a key trustworthy: any
+/// key, including a verifier key, must be authenticated, e.g. by a pinned digest or a signature.
+pub trait KeySerialization: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Deserializes a key. The bytes must come from a trusted source, since the digest does not
+    /// protect against a malicious key and the G1 points are not checked to be in the subgroup.
+    fn from_bytes(bytes: &[u8]) -> FastCryptoResult<Self>;
+
+    fn save<P: AsRef<Path>>(&self, path: P) -> FastCryptoResult<()> {
+        std::fs::write(path, self.to_bytes())
+            .map_err(|e| FastCryptoError::GeneralError(e.to_string()))
+    }
+
+    /// Loads a key saved with [KeySerialization::save]. As for [KeySerialization::from_bytes], the
+    /// file must come from a trusted source.
+    fn load<P: AsRef<Path>>(path: P) -> FastCryptoResult<Self> {
+        let bytes =
+            std::fs::read(path).map_err(|e| FastCryptoError::GeneralError(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Writes the serialization of a key.
+pub(crate) struct KeyWriter {
+    bytes: Vec<u8>,
+}
+
+impl KeyWriter {
+    pub(crate) fn new(scheme: u8, domain_size: usize) -> Self {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(scheme);
+        bytes.extend_from_slice(&(domain_size as u64).to_le_bytes());
+        Self { bytes }
+    }
+
+    pub(crate) fn write_g2(&mut self, p: &G2Element) {
+        self.bytes.extend_from_slice(&p.to_byte_array());
+    }
+
+    pub(crate) fn write_g1_vec(&mut self, v: &[G1Element]) {
+        self.bytes
+            .extend_from_slice(&(v.len() as u64).to_le_bytes());
+        for p in v {
+            self.bytes.extend_from_slice(&p.to_byte_array());
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        let digest = Sha256::digest(&self.bytes);
+        self.bytes.extend_from_slice(&digest.digest);
+        self.bytes
+    }
+}
+
+/// Reads the serialization of a key written by [KeyWriter]. G1 points are read without a subgroup
+/// check, so the digest only guards against corruption and the bytes must be trusted.
+pub(crate) struct KeyReader<'a> {
+    bytes: &'a [u8],
+    domain_size: usize,
+}
+
+impl<'a> KeyReader<'a> {
+    /// Checks the digest and the header of the serialization.
+    pub(crate) fn new(bytes: &'a [u8], scheme: u8) -> FastCryptoResult<Self> {
+        let header_length = MAGIC.len() + 2 + 8;
+        if bytes.len() < header_length + DIGEST_LENGTH {
+            return Err(FastCryptoError::InputTooShort(
+                header_length + DIGEST_LENGTH,
+            ));
+        }
+        let (bytes, digest) = bytes.split_at(bytes.len() - DIGEST_LENGTH);
+        if Sha256::digest(bytes).digest != digest {
+            return Err(FastCryptoError::InvalidInput);
+        }
+
+        let (header, bytes) = bytes.split_at(header_length);
+        if &header[..MAGIC.len()] != MAGIC
+            || header[MAGIC.len()] != VERSION
+            || header[MAGIC.len() + 1] != scheme
+        {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        let domain_size = u64::from_le_bytes(header[MAGIC.len() + 2..].try_into().unwrap());
+
+        Ok(Self {
+            bytes,
+            domain_size: domain_size as usize,
+        })
+    }
+
+    /// Returns the domain of the key, which must have exactly the serialized size.
+    pub(crate) fn domain(&self) -> FastCryptoResult<BLS12381Domain> {
+        let domain = BLS12381Domain::new(self.domain_size)?;
+        if domain.size() != self.domain_size {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        Ok(domain)
+    }
+
+    fn take(&mut self, length: usize) -> FastCryptoResult<&'a [u8]> {
+        if self.bytes.len() < length {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads a G2 element, which is checked to be in the prime order subgroup.
+    pub(crate) fn read_g2(&mut self) -> FastCryptoResult<G2Element> {
+        let bytes = self.take(G2_ELEMENT_BYTE_LENGTH)?;
+        G2Element::from_byte_array(bytes.try_into().unwrap())
+    }
+
+    /// Reads a vector of G1 elements, which must have the same length as the domain.
+    pub(crate) fn read_g1_vec(&mut self) -> FastCryptoResult<Vec<G1Element>> {
+        let length = u64::from_le_bytes(self.take(8)?.try_into().unwrap()) as usize;
+        if length != self.domain_size {
+            return Err(FastCryptoError::InputLengthWrong(self.domain_size));
+        }
+        let byte_length = length
+            .checked_mul(G1_ELEMENT_BYTE_LENGTH)
+            .ok_or(FastCryptoError::InvalidInput)?;
+        self.take(byte_length)?
+            .chunks_exact(G1_ELEMENT_BYTE_LENGTH)
+            .map(|chunk| G1Element::from_trusted_byte_array(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Checks that the whole serialization has been read.
+    pub(crate) fn finish(self) -> FastCryptoResult<()> {
+        if !self.bytes.is_empty() {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing};
+
+use crate::commitment::Commitment;
+use crate::fft::{BLS12381Domain, FFTDomain};
+use crate::polynomial::{
+    batch_inverse, evaluate, interpolate, polynomial_division, vanishing_polynomial,
+};
+use crate::srs::SRS;
+use crate::transcript::Transcript;
+use crate::verifier_key::KZGVerifierKey;
+use crate::{check_length, check_size, ProverKey};
+
+const DOMAIN_SEPARATION_TAG: &[u8] = b"KZG-SHPLONK-V1";
+
+/// A batched opening of several committed vectors, each at its own set of arbitrary points, as in
+/// BDFG20 (https://eprint.iacr.org/2020/081). It consists of two elements of G1 regardless of the
+/// number of vectors and points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShplonkProof {
+    w: G1Element,
+    w_prime: G1Element,
+}
+
+impl ShplonkProof {
+    pub fn new(w: G1Element, w_prime: G1Element) -> Self {
+        Self { w, w_prime }
+    }
+
+    /// Get the commitment to the combined quotient h(X).
+    pub fn w(&self) -> &G1Element {
+        &self.w
+    }
+
+    /// Get the opening of the linearized polynomial at the challenge z.
+    pub fn w_prime(&self) -> &G1Element {
+        &self.w_prime
+    }
+}
+
+/// Prover key for batched openings at arbitrary points. It only needs the powers of tau in G1, so
+/// proofs can be verified against commitments made with any of the schemes for the same SRS.
+#[derive(Clone)]
+pub struct Shplonk {
+    domain: BLS12381Domain,
+    tau_powers_g1: Vec<G1Element>,
+    g2_tau: G2Element,
+}
+
+impl Shplonk {
+    pub fn from_srs(n: usize, srs: &SRS) -> FastCryptoResult<Self> {
+        let domain = BLS12381Domain::new(n)?;
+        let tau_powers_g1 = srs.powers_g1(domain.size())?.to_vec();
+        Ok(Self {
+            domain,
+            tau_powers_g1,
+            g2_tau: *srs.g2_tau(),
+        })
+    }
+
+    pub fn verifier_key(&self) -> KZGVerifierKey {
+        KZGVerifierKey::new(&self.domain, self.g2_tau)
+    }
+
+    /// Opens the vectors v_k, with commitments C_k, at the points S_k and returns the values of the
+    /// polynomial interpolating each v_k over the domain at the points in S_k together with a
+    /// proof. The points in each S_k must be distinct, but may lie inside or outside the domain.
+    ///
+    /// With a challenge gamma, the prover commits to h(X) = sum_k gamma^k (f_k - r_k)(X) / Z_k(X)
+    /// where r_k interpolates the values of f_k on S_k and Z_k vanishes on S_k. With a second
+    /// challenge z, the polynomial L(X) = sum_k gamma^k (f_k(X) - r_k(z)) / Z_k(z) - h(X)
+    /// vanishes at z, and the proof is completed by an opening of L at z.
+    pub fn open<K: ProverKey<G = G1Element>>(
+        &self,
+        commitments: &[Commitment<K>],
+        vectors: &[Vec<Scalar>],
+        points: &[Vec<Scalar>],
+    ) -> FastCryptoResult<(Vec<Vec<Scalar>>, ShplonkProof)> {
+        let n = self.domain.size();
+        if commitments.is_empty()
+            || commitments.len() != vectors.len()
+            || commitments.len() != points.len()
+        {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        for commitment in commitments {
+            check_size(commitment.size(), n)?;
+        }
+
+        let polynomials = vectors
+            .iter()
+            .map(|v| {
+                check_length(v, n)?;
+                Ok(self.domain.ifft(v))
+            })
+            .collect::<FastCryptoResult<Vec<_>>>()?;
+        let values: Vec<Vec<Scalar>> = polynomials
+            .iter()
+            .zip(points)
+            .map(|(f, s)| s.iter().map(|x| evaluate(f, x)).collect())
+            .collect();
+        let remainders = points
+            .iter()
+            .zip(&values)
+            .map(|(s, y)| interpolate(s, y))
+            .collect::<FastCryptoResult<Vec<_>>>()?;
+
+        let mut transcript = statement(commitments, points, &values);
+        let gamma_powers = powers(&transcript.challenge(), commitments.len());
+
+        // h(X) = sum_k gamma^k (f_k - r_k)(X) / Z_k(X). The divisions are exact by construction.
+        let mut h = vec![Scalar::zero(); n];
+        for (((f, r), s), gamma_k) in polynomials
+            .iter()
+            .zip(&remainders)
+            .zip(points)
+            .zip(&gamma_powers)
+        {
+            let mut numerator = f.clone();
+            for (c, r_i) in numerator.iter_mut().zip(r) {
+                *c -= r_i;
+            }
+            let (quotient, _) = polynomial_division(&numerator, &vanishing_polynomial(s))?;
+            for (h_i, q_i) in h.iter_mut().zip(quotient) {
+                *h_i += q_i * gamma_k;
+            }
+        }
+        let w = self.commit_coefficients(&h)?;
+
+        transcript.append_g1(&w);
+        let z = transcript.challenge();
+        let weights = linearization_weights(points, &gamma_powers, &z)?;
+
+        // L(X) = sum_k gamma^k / Z_k(z) (f_k(X) - r_k(z)) - h(X).
+        let mut l: Vec<Scalar> = h.iter().map(|h_i| -*h_i).collect();
+        for ((f, r), weight) in polynomials.iter().zip(&remainders).zip(&weights) {
+            for (l_i, f_i) in l.iter_mut().zip(f) {
+                *l_i += f_i * weight;
+            }
+            l[0] -= evaluate(r, &z) * weight;
+        }
+        let (quotient, _) = polynomial_division(&l, &[-z, Scalar::generator()])?;
+        let w_prime = self.commit_coefficients(&quotient)?;
+
+        Ok((values, ShplonkProof::new(w, w_prime)))
+    }
+
+    fn commit_coefficients(&self, coefficients: &[Scalar]) -> FastCryptoResult<G1Element> {
+        if coefficients.is_empty() {
+            return Ok(G1Element::zero());
+        }
+        G1Element::multi_scalar_mul(coefficients, &self.tau_powers_g1[..coefficients.len()])
+    }
+}
+
+impl KZGVerifierKey {
+    /// Verifies a proof from [Shplonk::open] that the vector committed to by C_k takes the values
+    /// y_k at the points S_k, by computing F = sum_k gamma^k / Z_k(z) (C_k - [r_k(z)]_1) - W, which
+    /// is a commitment to L, and checking that e(F + z W', [1]_2) = e(W', [tau]_2).
+    pub fn verify_shplonk<K: ProverKey<G = G1Element>>(
+        &self,
+        commitments: &[Commitment<K>],
+        points: &[Vec<Scalar>],
+        values: &[Vec<Scalar>],
+        proof: &ShplonkProof,
+    ) -> bool {
+        if commitments.is_empty()
+            || commitments.len() != points.len()
+            || commitments.len() != values.len()
+            || commitments.iter().any(|c| c.size() != self.size())
+        {
+            return false;
+        }
+        self.check_shplonk(commitments, points, values, proof)
+            .unwrap_or(false)
+    }
+
+    fn check_shplonk<K: ProverKey<G = G1Element>>(
+        &self,
+        commitments: &[Commitment<K>],
+        points: &[Vec<Scalar>],
+        values: &[Vec<Scalar>],
+        proof: &ShplonkProof,
+    ) -> FastCryptoResult<bool> {
+        let mut transcript = statement(commitments, points, values);
+        let gamma_powers = powers(&transcript.challenge(), commitments.len());
+        transcript.append_g1(proof.w());
+        let z = transcript.challenge();
+        let weights = linearization_weights(points, &gamma_powers, &z)?;
+
+        let mut r_at_z = Scalar::zero();
+        for ((s, y), weight) in points.iter().zip(values).zip(&weights) {
+            r_at_z += evaluate(&interpolate(s, y)?, &z) * weight;
+        }
+
+        let mut scalars = weights;
+        scalars.extend([-r_at_z, -Scalar::generator(), z]);
+        let mut elements: Vec<G1Element> = commitments.iter().map(|c| *c.element()).collect();
+        elements.extend([G1Element::generator(), *proof.w(), *proof.w_prime()]);
+        let lhs = G1Element::multi_scalar_mul(&scalars, &elements)?;
+
+        Ok(lhs.pairing(&G2Element::generator()) == proof.w_prime().pairing(self.g2_tau()))
+    }
+}
+
+/// Returns 1, x, ..., x^{k-1}.
+fn powers(x: &Scalar, k: usize) -> Vec<Scalar> {
+    itertools::iterate(Scalar::generator(), |p| p * x)
+        .take(k)
+        .collect()
+}
+
+/// Returns gamma^k / Z_k(z) for all k, where Z_k vanishes on the k-th set of points. Fails in the
+/// negligibly likely case that z is one of the points.
+fn linearization_weights(
+    points: &[Vec<Scalar>],
+    gamma_powers: &[Scalar],
+    z: &Scalar,
+) -> FastCryptoResult<Vec<Scalar>> {
+    let vanishing_at_z: Vec<Scalar> = points
+        .iter()
+        .map(|s| s.iter().fold(Scalar::generator(), |acc, x| acc * (z - x)))
+        .collect();
+    Ok(batch_inverse(&vanishing_at_z)?
+        .into_iter()
+        .zip(gamma_powers)
+        .map(|(inverse, gamma_k)| inverse * gamma_k)
+        .collect())
+}
+
+/// Starts a transcript with the statement: the commitments, the points and the claimed values.
+fn statement<K: ProverKey<G = G1Element>>(
+    commitments: &[Commitment<K>],
+    points: &[Vec<Scalar>],
+    values: &[Vec<Scalar>],
+) -> Transcript {
+    let mut transcript = Transcript::new(DOMAIN_SEPARATION_TAG);
+    transcript.append_usize(commitments.len());
+    for ((commitment, s), y) in commitments.iter().zip(points).zip(values) {
+        transcript.append_g1(commitment.element());
+        transcript.append_usize(s.len());
+        for x in s.iter().chain(y) {
+            transcript.append_scalar(x);
+        }
+    }
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::groups::Scalar as OtherScalar;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+    use crate::kzg_original::KZGOriginal;
+    use crate::KZG;
+
+    fn random_vector(n: usize) -> Vec<Scalar> {
+        (0..n).map(|_| Scalar::rand(&mut thread_rng())).collect()
+    }
+
+    #[test]
+    fn test_open_verify() {
+        let n = 8;
+        let tau = Scalar::rand(&mut thread_rng());
+        let srs = SRS::from_tau(n, &tau);
+        let shplonk = Shplonk::from_srs(n, &srs).unwrap();
+        let vk = shplonk.verifier_key();
+        let domain = BLS12381Domain::new(n).unwrap();
+
+        // Commitments from different schemes are to the same polynomials.
+        let kzg = KZGDeriv::from_srs(n, &srs).unwrap();
+        let vectors = vec![random_vector(n), random_vector(n), random_vector(n)];
+        let commitments: Vec<_> = vectors.iter().map(|v| kzg.commit(v).unwrap()).collect();
+        let points = vec![
+            random_vector(2),
+            vec![domain.element(3)],
+            vec![Scalar::rand(&mut thread_rng()), domain.element(1)],
+        ];
+
+        let (values, proof) = shplonk.open(&commitments, &vectors, &points).unwrap();
+        assert_eq!(values[1], vec![vectors[1][3]]);
+        assert_eq!(values[2][1], vectors[2][1]);
+        assert!(vk.verify_shplonk(&commitments, &points, &values, &proof));
+
+        // A wrong value, point or proof is rejected.
+        let mut wrong_values = values.clone();
+        wrong_values[0][1] += Scalar::generator();
+        assert!(!vk.verify_shplonk(&commitments, &points, &wrong_values, &proof));
+        let mut wrong_points = points.clone();
+        wrong_points[2][0] += Scalar::generator();
+        assert!(!vk.verify_shplonk(&commitments, &wrong_points, &values, &proof));
+        let wrong_proof = ShplonkProof::new(*proof.w_prime(), *proof.w());
+        assert!(!vk.verify_shplonk(&commitments, &points, &values, &wrong_proof));
+        assert!(!vk.verify_shplonk(&commitments[1..], &points[1..], &values[1..], &proof));
+
+        let original = KZGOriginal::from_srs(n, &srs).unwrap();
+        let commitment = original.commit(&vectors[0]).unwrap();
+        let (values, proof) = shplonk
+            .open(&[commitment], &vectors[..1], &points[..1])
+            .unwrap();
+        assert!(vk.verify_shplonk(&[commitment], &points[..1], &values, &proof));
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        let n = 4;
+        let srs = SRS::from_tau(n, &Scalar::rand(&mut thread_rng()));
+        let shplonk = Shplonk::from_srs(n, &srs).unwrap();
+        let kzg = KZGDeriv::from_srs(n, &srs).unwrap();
+        let vectors = vec![random_vector(n)];
+        let commitment = kzg.commit(&vectors[0]).unwrap();
+        let x = Scalar::rand(&mut thread_rng());
+
+        assert!(shplonk.open::<KZGDeriv>(&[], &[], &[]).is_err());
+        assert!(shplonk
+            .open(&[commitment], &vectors, &[vec![x, x]])
+            .is_err());
+        assert!(shplonk
+            .open(&[commitment, commitment], &vectors, &[vec![x]])
+            .is_err());
+        assert!(shplonk
+            .open(
+                &[Commitment::<KZGDeriv>::new(*commitment.element(), 8)],
+                &vectors,
+                &[vec![x]]
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::Mul;
+use std::sync::OnceLock;
+
+use fastcrypto::error::{FastCryptoError, FastCryptoResult};
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
+use fastcrypto::groups::GroupElement;
+
+use crate::fft::{BLS12381Domain, FFTDomain};
+
+/// Structured reference string produced by a trusted setup: the powers [tau^i]_1 for i = 0 to d-1
+/// together with the powers [tau^i]_2 for i = 0 to e-1, where e >= 2 and typically e is much
+/// smaller than d. The schemes derive all their precomputed vectors from these points, so tau
+/// itself never has to be known.
+#[derive(Clone)]
+pub struct SRS {
+    tau_powers_g1: Vec<G1Element>,
+    tau_powers_g2: Vec<G2Element>,
+}
+
+impl SRS {
+    /// Creates a new SRS from externally supplied powers of tau in G1 and [tau]_2. The first power
+    /// must be the generator of G1.
+    pub fn new(tau_powers_g1: Vec<G1Element>, g2_tau: G2Element) -> FastCryptoResult<Self> {
+        Self::with_g2_powers(tau_powers_g1, vec![G2Element::generator(), g2_tau])
+    }
+
+    /// Creates a new SRS from externally supplied powers of tau in both G1 and G2. The first
+    /// powers must be the generators and at least [tau]_2 must be given.
+    pub fn with_g2_powers(
+        tau_powers_g1: Vec<G1Element>,
+        tau_powers_g2: Vec<G2Element>,
+    ) -> FastCryptoResult<Self> {
+        if tau_powers_g1.first() != Some(&G1Element::generator())
+            || tau_powers_g2.len() < 2
+            || tau_powers_g2[0] != G2Element::generator()
+        {
+            return Err(FastCryptoError::InvalidInput);
+        }
+        Ok(Self {
+            tau_powers_g1,
+            tau_powers_g2,
+        })
+    }
+
+    /// Creates an SRS with n powers in G1 from a known tau. Anyone knowing tau can forge openings,
+    /// so this is only meant for testing and benchmarking.
+    pub(crate) fn from_tau(n: usize, tau: &Scalar) -> Self {
+        Self::from_tau_with_g2_powers(n, 2, tau)
+    }
+
+    /// Like [SRS::from_tau] but with n_g2 powers in G2.
+    pub(crate) fn from_tau_with_g2_powers(n: usize, n_g2: usize, tau: &Scalar) -> Self {
+        let tau_powers_g1 = itertools::iterate(G1Element::generator(), |g| g.mul(tau))
+            .take(n)
+            .collect();
+        let tau_powers_g2 = itertools::iterate(G2Element::generator(), |g| g.mul(tau))
+            .take(n_g2)
+            .collect();
+        Self {
+            tau_powers_g1,
+            tau_powers_g2,
+        }
+    }
+
+    pub fn tau_powers_g1(&self) -> &[G1Element] {
+        &self.tau_powers_g1
+    }
+
+    pub fn tau_powers_g2(&self) -> &[G2Element] {
+        &self.tau_powers_g2
+    }
+
+    pub fn g2_tau(&self) -> &G2Element {
+        &self.tau_powers_g2[1]
+    }
+
+    /// Returns [L_i(tau)]_1 for all i, where L_i is the i-th Lagrange polynomial of the domain.
+    /// This is the commitment key for vectors in evaluation form. It is computed with an IFFT over
+    /// the powers of tau in G1, so it can be derived from any public transcript.
+    pub fn lagrange_basis_g1(&self, domain: &BLS12381Domain) -> FastCryptoResult<Vec<G1Element>> {
+        Ok(lagrange_basis(domain, self.powers_g1(domain.size())?))
+    }
+
+    /// Returns [(L_i(tau) - 1) / (tau - omega^i)]_1 for all i, the hints used to update the opening
+    /// at index i when the value at index i changes.
+    ///
+    /// The j-th coefficient of (L_i(X) - 1) / (X - omega^i) is (n - 1 - j) / n * omega^{-i(j+1)},
+    /// so the hints are omega^{-i} times the IFFT of ((n - 1 - j) [tau^j]_1)_j. This only needs the
+    /// powers of tau in G1 and costs a single group IFFT instead of an inversion per index.
+    pub fn lagrange_quotients_g1(
+        &self,
+        domain: &BLS12381Domain,
+    ) -> FastCryptoResult<Vec<G1Element>> {
+        Ok(lagrange_quotients(domain, self.powers_g1(domain.size())?))
+    }
+
+    /// Returns the first n powers of tau in G1, or an error if the SRS has fewer than n powers.
+    pub(crate) fn powers_g1(&self, n: usize) -> FastCryptoResult<&[G1Element]> {
+        self.tau_powers_g1
+            .get(..n)
+            .ok_or(FastCryptoError::InputTooShort(n))
+    }
+
+    /// Returns the first n powers of tau in G2, or an error if the SRS has fewer than n powers.
+    pub(crate) fn powers_g2(&self, n: usize) -> FastCryptoResult<&[G2Element]> {
+        self.tau_powers_g2
+            .get(..n)
+            .ok_or(FastCryptoError::InputTooShort(n))
+    }
+}
+
+/// Computes [SRS::lagrange_basis_g1] from the first n powers of tau in G1.
+fn lagrange_basis(domain: &BLS12381Domain, tau_powers_g1: &[G1Element]) -> Vec<G1Element> {
+    let mut l_vec = tau_powers_g1.to_vec();
+    domain.ifft_in_place_group(&mut l_vec);
+    l_vec
+}
+
+/// Computes [SRS::lagrange_quotients_g1] from the first n powers of tau in G1.
+fn lagrange_quotients(domain: &BLS12381Domain, tau_powers_g1: &[G1Element]) -> Vec<G1Element> {
+    let n = domain.size();
+    let mut u_vec: Vec<G1Element> = tau_powers_g1
+        .iter()
+        .enumerate()
+        .map(|(j, p)| p.mul(Scalar::from((n - 1 - j) as u128)))
+        .collect();
+    domain.ifft_in_place_group(&mut u_vec);
+    for (i, u_i) in u_vec.iter_mut().enumerate() {
+        *u_i = u_i.mul(domain.inverse_element(i));
+    }
+    u_vec
+}
+
+/// The Lagrange basis and the hints of [SRS::lagrange_quotients_g1], which schemes committing in
+/// coefficient form only need for updates. They cost two group IFFTs, so they are computed from
+/// the powers of tau the first time an update needs them.
+#[derive(Clone, Default)]
+pub(crate) struct UpdateHints(OnceLock<(Vec<G1Element>, Vec<G1Element>)>);
+
+impl UpdateHints {
+    /// Returns the Lagrange basis and the hints, computing them from the first n powers of tau in
+    /// G1 on the first call.
+    pub(crate) fn get(
+        &self,
+        domain: &BLS12381Domain,
+        tau_powers_g1: &[G1Element],
+    ) -> (&[G1Element], &[G1Element]) {
+        let (l_vec, u_vec) = self.0.get_or_init(|| {
+            (
+                lagrange_basis(domain, tau_powers_g1),
+                lagrange_quotients(domain, tau_powers_g1),
+            )
+        });
+        (l_vec, u_vec)
+    }
+}
+
+/// Computes [(tau^n - 1) / (tau - omega^i)]_1 for all i from the Lagrange basis, using that
+/// (X^n - 1) / (X - omega^i) = n * omega^{-i} * L_i(X). These are the hints used to update the
+/// opening at index i when the value at another index changes.
+pub(crate) fn vanishing_quotients_g1(
+    domain: &BLS12381Domain,
+    l_vec: &[G1Element],
+) -> Vec<G1Element> {
+    let n = domain.size();
+    let n_scalar = Scalar::from(n as u128);
+    l_vec
+        .iter()
+        .enumerate()
+        .map(|(i, l_i)| l_i.mul(n_scalar * domain.inverse_element(i)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::groups::Scalar as OtherScalar;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_srs_requires_generator() {
+        let tau = Scalar::rand(&mut thread_rng());
+        let srs = SRS::from_tau(4, &tau);
+        assert!(SRS::new(srs.tau_powers_g1().to_vec(), *srs.g2_tau()).is_ok());
+        assert!(SRS::new(srs.tau_powers_g1()[1..].to_vec(), *srs.g2_tau()).is_err());
+        assert!(SRS::new(vec![], *srs.g2_tau()).is_err());
+        assert!(srs.powers_g1(5).is_err());
+
+        let srs = SRS::from_tau_with_g2_powers(4, 3, &tau);
+        let g1_powers = srs.tau_powers_g1().to_vec();
+        assert_eq!(srs.tau_powers_g2()[1], *srs.g2_tau());
+        assert!(SRS::with_g2_powers(g1_powers.clone(), srs.tau_powers_g2().to_vec()).is_ok());
+        assert!(SRS::with_g2_powers(g1_powers.clone(), srs.tau_powers_g2()[..1].to_vec()).is_err());
+        assert!(SRS::with_g2_powers(g1_powers, srs.tau_powers_g2()[1..].to_vec()).is_err());
+        assert!(srs.powers_g2(4).is_err());
+    }
+
+    #[test]
+    fn test_derived_vectors_match_tau() {
+        let n = 8;
+        let domain = BLS12381Domain::new(n).unwrap();
+        let tau = Scalar::rand(&mut thread_rng());
+        let srs = SRS::from_tau(n, &tau);
+        let g = G1Element::generator();
+
+        let l_vec = srs.lagrange_basis_g1(&domain).unwrap();
+        let u_vec = srs.lagrange_quotients_g1(&domain).unwrap();
+        let a_vec = vanishing_quotients_g1(&domain, &l_vec);
+        assert_eq!(
+            UpdateHints::default().get(&domain, srs.tau_powers_g1()),
+            (l_vec.as_slice(), u_vec.as_slice())
+        );
+
+        let tau_powers: Vec<Scalar> = itertools::iterate(Scalar::generator(), |t| t * tau)
+            .take(n)
+            .collect();
+        let lagrange = domain.ifft(&tau_powers);
+        let tau_n_minus_1 = tau_powers[n - 1] * tau - Scalar::generator();
+
+        for i in 0..n {
+            let denom_inverse = (tau - domain.element(i)).inverse().unwrap();
+            assert_eq!(l_vec[i], g.mul(lagrange[i]));
+            assert_eq!(
+                u_vec[i],
+                g.mul((lagrange[i] - Scalar::generator()) * denom_inverse)
+            );
+            assert_eq!(a_vec[i], g.mul(tau_n_minus_1 * denom_inverse));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use fastcrypto::error::FastCryptoResult;
+use fastcrypto::groups::bls12381::{G1Element, Scalar};
+
+use crate::commitment::{Commitment, Opening};
+use crate::{check_index, KZG};
+
+/// Owns a vector together with its commitment and the openings at all indices, and keeps them in
+/// sync when the vector is written to.
+///
+/// The commitment is updated on every write. The openings are updated lazily: writes are logged,
+/// and an opening is brought up to date with [crate::ProverKey::update_open_i] and
+/// [crate::ProverKey::update_open_j] when it is read. Once more than max_pending writes have been
+/// logged, the cached openings are dropped and all are recomputed with
+/// [crate::ProverKey::open_all] on the next read instead.
+pub struct VectorCommitmentStore<K: KZG<G = G1Element>> {
+    kzg: K,
+    values: Vec<Scalar>,
+    commitment: Commitment<K>,
+    // Empty until computed by open_all
+    openings: Vec<Opening<K>>,
+    // The number of logged writes applied to each of the openings
+    applied: Vec<usize>,
+    // Writes (index, old_v, new_v) since the openings were computed
+    log: Vec<(usize, Scalar, Scalar)>,
+    max_pending: usize,
+}
+
+impl<K: KZG<G = G1Element>> VectorCommitmentStore<K> {
+    /// Commits to the values, one for each element of the domain. The openings are only computed
+    /// on the first read.
+    pub fn new(kzg: K, values: &[Scalar], max_pending: usize) -> FastCryptoResult<Self> {
+        let commitment = kzg.commit(values)?;
+        Ok(Self {
+            kzg,
+            values: values.to_vec(),
+            commitment,
+            openings: vec![],
+            applied: vec![],
+            log: vec![],
+            max_pending,
+        })
+    }
+
+    pub fn commitment(&self) -> &Commitment<K> {
+        &self.commitment
+    }
+
+    pub fn values(&self) -> &[Scalar] {
+        &self.values
+    }
+
+    /// Get the size of the domain, which is the number of values.
+    pub fn size(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the value at index and an opening of the current commitment at index.
+    pub fn get(&mut self, index: usize) -> FastCryptoResult<(Scalar, Opening<K>)> {
+        check_index(index, self.size())?;
+        if self.openings.is_empty() {
+            self.openings = self.kzg.open_all(&self.values)?;
+            self.applied = vec![0; self.openings.len()];
+            self.log.clear();
+        }
+
+        let mut open = self.openings[index];
+        for (j, old_v_j, new_v_j) in &self.log[self.applied[index]..] {
+            open = if *j == index {
+                self.kzg.update_open_i(&open, index, old_v_j, new_v_j)?
+            } else {
+                self.kzg.update_open_j(&open, index, *j, old_v_j, new_v_j)?
+            };
+        }
+        self.openings[index] = open;
+        self.applied[index] = self.log.len();
+        Ok((self.values[index], open))
+    }
+
+    /// Sets the value at index and updates the commitment.
+    pub fn set(&mut self, index: usize, value: Scalar) -> FastCryptoResult<()> {
+        check_index(index, self.size())?;
+        let old_value = self.values[index];
+        self.commitment = self
+            .kzg
+            .update(&self.commitment, index, &old_value, &value)?;
+        self.values[index] = value;
+
+        if !self.openings.is_empty() {
+            self.log.push((index, old_value, value));
+            if self.log.len() > self.max_pending {
+                self.openings.clear();
+                self.applied.clear();
+                self.log.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::groups::{GroupElement, Scalar as OtherScalar};
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+    use crate::kzg_fk::KZGFK;
+    use crate::VerifierKey;
+
+    fn check_store<K: KZG<G = G1Element>>(max_pending: usize) {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = K::new(n).unwrap();
+        let vk = kzg.verifier_key();
+        let values: Vec<Scalar> = (0..n).map(|_| Scalar::rand(&mut rng)).collect();
+        let mut store = VectorCommitmentStore::new(kzg, &values, max_pending).unwrap();
+        assert_eq!(store.size(), n);
+
+        for _ in 0..20 {
+            let index = rng.gen_range(0..n);
+            if rng.gen_bool(0.5) {
+                store.set(index, Scalar::rand(&mut rng)).unwrap();
+            }
+            let (value, open) = store.get(index).unwrap();
+            assert_eq!(value, store.values()[index]);
+            assert!(vk.verify(index, &value, store.commitment(), &open));
+        }
+
+        for index in 0..n {
+            let (value, open) = store.get(index).unwrap();
+            assert!(vk.verify(index, &value, store.commitment(), &open));
+        }
+        assert!(store.get(n).is_err());
+        assert!(VectorCommitmentStore::new(K::new(n).unwrap(), &values[1..], max_pending).is_err());
+        assert!(store.set(n, Scalar::zero()).is_err());
+    }
+
+    #[test]
+    fn test_store() {
+        check_store::<KZGDeriv>(4);
+        check_store::<KZGDeriv>(100);
+        check_store::<KZGFK>(0);
+        check_store::<KZGFK>(4);
+    }
+}
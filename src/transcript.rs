@@ -0,0 +1,40 @@
+use fastcrypto::groups::bls12381::{G1Element, Scalar};
+use fastcrypto::groups::FiatShamirChallenge;
+use fastcrypto::hash::{HashFunction, Sha512};
+use fastcrypto::serde_helpers::ToFromByteArray;
+
+/// Fiat-Shamir transcript. Challenges are derived by hashing everything appended so far with
+/// SHA-512 and reducing the digest modulo the group order.
+pub(crate) struct Transcript {
+    bytes: Vec<u8>,
+}
+
+impl Transcript {
+    /// Starts a transcript with a tag which separates the protocol from others.
+    pub(crate) fn new(domain_separation_tag: &[u8]) -> Self {
+        Self {
+            bytes: domain_separation_tag.to_vec(),
+        }
+    }
+
+    /// Appends a length or an index.
+    pub(crate) fn append_usize(&mut self, x: usize) {
+        self.bytes.extend_from_slice(&(x as u64).to_le_bytes());
+    }
+
+    pub(crate) fn append_scalar(&mut self, x: &Scalar) {
+        self.bytes.extend_from_slice(&x.to_byte_array());
+    }
+
+    pub(crate) fn append_g1(&mut self, p: &G1Element) {
+        self.bytes.extend_from_slice(&p.to_byte_array());
+    }
+
+    /// Derives a challenge and appends it to the transcript.
+    pub(crate) fn challenge(&mut self) -> Scalar {
+        let digest = Sha512::digest(&self.bytes).digest;
+        let challenge = Scalar::fiat_shamir_reduction_to_group_element(&digest);
+        self.append_scalar(&challenge);
+        challenge
+    }
+}
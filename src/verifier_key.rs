@@ -0,0 +1,577 @@
+use fastcrypto::error::FastCryptoResult;
+use fastcrypto::groups::bls12381::{G1Element, G2Element, Scalar};
+use fastcrypto::groups::{GroupElement, MultiScalarMul, Pairing, Scalar as OtherScalar};
+use fastcrypto::traits::AllowedRng;
+
+use crate::commitment::{Commitment, Opening};
+use crate::fft::{BLS12381Domain, FFTDomain};
+use crate::polynomial::{interpolate, pow, vanishing_polynomial};
+use crate::serialize::{KeyReader, KeySerialization, KeyWriter, KZG_VERIFIER};
+use crate::srs::SRS;
+use crate::{check_subset, open_many_challenge, ProverKey, VerifierKey};
+
+/// Verifier key shared by all the KZG schemes over BLS12-381. It only holds [tau]_2 and the
+/// generator and size of the domain, so it can be handed to light clients that never commit or
+/// open themselves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KZGVerifierKey {
+    g2_tau: G2Element,
+    omega: Scalar,
+    n: usize,
+}
+
+impl KZGVerifierKey {
+    pub fn new(domain: &BLS12381Domain, g2_tau: G2Element) -> Self {
+        Self {
+            g2_tau,
+            omega: domain.element(1),
+            n: domain.size(),
+        }
+    }
+
+    pub fn g2_tau(&self) -> &G2Element {
+        &self.g2_tau
+    }
+
+    /// Get the size of the domain.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Wraps a commitment received as a group element, e.g. from a peer, for verification with
+    /// this key. It is tied to the domain of this key, so it only verifies against keys for the
+    /// same domain size.
+    pub fn commitment_from_element<K: ProverKey<G = G1Element>>(
+        &self,
+        element: G1Element,
+    ) -> Commitment<K> {
+        Commitment::new(element, self.n)
+    }
+
+    /// Wraps an opening received as a group element, e.g. from a peer, for verification with this
+    /// key, like [KZGVerifierKey::commitment_from_element].
+    pub fn opening_from_element<K: ProverKey<G = G1Element>>(
+        &self,
+        element: G1Element,
+    ) -> Opening<K> {
+        Opening::new(element, self.n)
+    }
+
+    /// Computes omega^index by repeated squaring.
+    pub fn element(&self, index: usize) -> Scalar {
+        pow(&self.omega, index % self.n)
+    }
+
+    /// Verifies a proof from [ProverKey::open_many] that the vectors committed to by C_1, ..., C_m
+    /// take the given values at the index. The challenge gamma is derived from the commitments, the
+    /// index and the values as by the prover, so it cannot be chosen by the prover, and the proof
+    /// is verified as an opening of sum_k gamma^k C_k.
+    pub fn verify_many<K: ProverKey<G = G1Element>>(
+        &self,
+        index: usize,
+        values: &[Scalar],
+        commitments: &[Commitment<K>],
+        proof: &Opening<K>,
+    ) -> bool {
+        if commitments.is_empty()
+            || commitments.len() != values.len()
+            || commitments.iter().any(|c| c.size() != self.n)
+        {
+            return false;
+        }
+        let challenge = open_many_challenge(commitments, index, values);
+        let commitment = commitments
+            .iter()
+            .rev()
+            .fold(G1Element::zero(), |acc, c| acc * challenge + c.element());
+        let value = values
+            .iter()
+            .rev()
+            .fold(Scalar::zero(), |acc, v_k| acc * challenge + v_k);
+        self.verify(index, &value, &Commitment::new(commitment, self.n), proof)
+    }
+}
+
+impl VerifierKey for KZGVerifierKey {
+    type G = G1Element;
+
+    /// Verifies a KZG opening by checking that e(C - [v_i]_1, [1]_2) = e(pi, [tau - omega^i]_2).
+    /// Openings at indices outside the domain are rejected.
+    fn verify<K: ProverKey<G = G1Element>>(
+        &self,
+        index: usize,
+        v_i: &Scalar,
+        commitment: &Commitment<K>,
+        open_i: &Opening<K>,
+    ) -> bool {
+        index < self.n && self.verify_at(&self.element(index), v_i, commitment, open_i)
+    }
+
+    /// Verifies an evaluation proof by checking that e(C - [y]_1, [1]_2) = e(pi, [tau - z]_2).
+    fn verify_at<K: ProverKey<G = G1Element>>(
+        &self,
+        z: &Scalar,
+        y: &Scalar,
+        commitment: &Commitment<K>,
+        proof: &Opening<K>,
+    ) -> bool {
+        if commitment.size() != self.n || proof.size() != self.n {
+            return false;
+        }
+        let lhs = *commitment.element() - G1Element::generator() * y;
+        let rhs = self.g2_tau - G2Element::generator() * z;
+
+        lhs.pairing(&G2Element::generator()) == proof.element().pairing(&rhs)
+    }
+
+    /// Each opening satisfies e(C - [v_i]_1 + omega^i pi_i, [1]_2) = e(pi_i, [tau]_2), so with
+    /// random weights r_i it suffices to check that
+    /// e(sum r_i (C - [v_i]_1 + omega^i pi_i), [1]_2) = e(sum r_i pi_i, [tau]_2).
+    fn verify_batch<K: ProverKey<G = G1Element>, R: AllowedRng>(
+        &self,
+        commitment: &Commitment<K>,
+        openings: &[(usize, Scalar, Opening<K>)],
+        rng: &mut R,
+    ) -> bool {
+        if commitment.size() != self.n
+            || openings
+                .iter()
+                .any(|(index, _, open_i)| !self.is_well_formed(*index, open_i.size()))
+        {
+            return false;
+        }
+        if openings.is_empty() {
+            return true;
+        }
+
+        let weights: Vec<Scalar> = openings.iter().map(|_| Scalar::rand(rng)).collect();
+        let weight_sum = weights.iter().fold(Scalar::zero(), |sum, r| sum + r);
+        let openings: Vec<(usize, &Scalar, &G1Element)> = openings
+            .iter()
+            .map(|(index, v_i, open_i)| (*index, v_i, open_i.element()))
+            .collect();
+        self.check_weighted(
+            vec![*commitment.element()],
+            vec![weight_sum],
+            &openings,
+            &weights,
+        )
+    }
+
+    /// Commitments are checked with the weights of their openings, i.e. the left hand side of the
+    /// combined check is sum r_i (C_i - [v_i]_1 + omega^{j_i} pi_i). If that check fails, the
+    /// entries are split in two halves which are checked separately, until the invalid entries
+    /// are found. Malformed entries are reported without doing any pairings.
+    fn verify_batch_multi<K: ProverKey<G = G1Element>, R: AllowedRng>(
+        &self,
+        entries: &[(Commitment<K>, usize, Scalar, Opening<K>)],
+        rng: &mut R,
+    ) -> Result<(), Vec<usize>> {
+        let (positions, mut invalid): (Vec<usize>, Vec<usize>) =
+            (0..entries.len()).partition(|&p| {
+                let (commitment, index, _, open_i) = &entries[p];
+                commitment.size() == self.n && self.is_well_formed(*index, open_i.size())
+            });
+
+        self.bisect(entries, &positions, rng, &mut invalid);
+        if invalid.is_empty() {
+            return Ok(());
+        }
+        invalid.sort_unstable();
+        Err(invalid)
+    }
+}
+
+impl KZGVerifierKey {
+    /// Returns true if the index is in the domain and the opening was made for this domain.
+    fn is_well_formed(&self, index: usize, size: usize) -> bool {
+        index < self.n && size == self.n
+    }
+
+    /// Checks that e(sum_k s_k P_k - (sum_i r_i v_i) [1]_1 + sum_i r_i omega^{j_i} pi_i, [1]_2)
+    /// equals e(sum_i r_i pi_i, [tau]_2) for openings (j_i, v_i, pi_i) with weights r_i, where
+    /// sum_k s_k P_k is the weighted sum of the commitments given by the points P_k and
+    /// scalars s_k.
+    fn check_weighted(
+        &self,
+        mut points: Vec<G1Element>,
+        mut scalars: Vec<Scalar>,
+        openings: &[(usize, &Scalar, &G1Element)],
+        weights: &[Scalar],
+    ) -> bool {
+        let proofs: Vec<G1Element> = openings.iter().map(|(_, _, pi)| **pi).collect();
+
+        points.push(G1Element::generator());
+        scalars.push(
+            -weights
+                .iter()
+                .zip(openings)
+                .fold(Scalar::zero(), |sum, (r, (_, v_i, _))| sum + r * *v_i),
+        );
+        points.extend_from_slice(&proofs);
+        scalars.extend(
+            weights
+                .iter()
+                .zip(openings)
+                .map(|(r, (index, _, _))| r * self.element(*index)),
+        );
+
+        match (
+            G1Element::multi_scalar_mul(&scalars, &points),
+            G1Element::multi_scalar_mul(weights, &proofs),
+        ) {
+            (Ok(lhs), Ok(rhs)) => lhs.pairing(&G2Element::generator()) == rhs.pairing(&self.g2_tau),
+            _ => false,
+        }
+    }
+
+    /// Adds the positions of the entries which do not verify to invalid, by checking all entries
+    /// at the given positions at once and recursing into both halves if that fails.
+    fn bisect<K: ProverKey<G = G1Element>, R: AllowedRng>(
+        &self,
+        entries: &[(Commitment<K>, usize, Scalar, Opening<K>)],
+        positions: &[usize],
+        rng: &mut R,
+        invalid: &mut Vec<usize>,
+    ) {
+        if positions.is_empty() {
+            return;
+        }
+
+        let weights: Vec<Scalar> = positions.iter().map(|_| Scalar::rand(rng)).collect();
+        let commitments = positions.iter().map(|&p| *entries[p].0.element()).collect();
+        let openings: Vec<(usize, &Scalar, &G1Element)> = positions
+            .iter()
+            .map(|&p| (entries[p].1, &entries[p].2, entries[p].3.element()))
+            .collect();
+        if self.check_weighted(commitments, weights.clone(), &openings, &weights) {
+            return;
+        }
+
+        if positions.len() == 1 {
+            invalid.push(positions[0]);
+            return;
+        }
+        let (left, right) = positions.split_at(positions.len() / 2);
+        self.bisect(entries, left, rng, invalid);
+        self.bisect(entries, right, rng, invalid);
+    }
+}
+
+/// Verifier key for openings of subsets of up to a fixed number of indices, as made by
+/// [ProverKey::open_subset]. Besides the [KZGVerifierKey] it needs the first powers of tau in G1,
+/// one for each index in the largest subset, and in G2, one more than that.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubsetVerifierKey {
+    key: KZGVerifierKey,
+    tau_powers_g1: Vec<G1Element>,
+    tau_powers_g2: Vec<G2Element>,
+}
+
+impl SubsetVerifierKey {
+    /// Creates a verifier key for subsets of at most max_size indices in a domain of n elements.
+    /// The SRS must have at least max_size powers in G1 and max_size + 1 powers in G2.
+    pub fn from_srs(n: usize, srs: &SRS, max_size: usize) -> FastCryptoResult<Self> {
+        let domain = BLS12381Domain::new(n)?;
+        Ok(Self {
+            key: KZGVerifierKey::new(&domain, *srs.g2_tau()),
+            tau_powers_g1: srs.powers_g1(max_size)?.to_vec(),
+            tau_powers_g2: srs.powers_g2(max_size + 1)?.to_vec(),
+        })
+    }
+
+    /// Get the verifier key for openings at a single index.
+    pub fn verifier_key(&self) -> &KZGVerifierKey {
+        &self.key
+    }
+
+    /// Get the largest number of indices in a subset this key can verify.
+    pub fn max_subset_size(&self) -> usize {
+        self.tau_powers_g1.len()
+    }
+
+    /// Verifies that the committed vector has the given values at the given indices by checking
+    /// that e(C - [I(tau)]_1, [1]_2) = e(pi, [Z_S(tau)]_2), where I interpolates the values and
+    /// Z_S vanishes on the points of the indices.
+    pub fn verify_subset<K: ProverKey<G = G1Element>>(
+        &self,
+        indices: &[usize],
+        values: &[Scalar],
+        commitment: &Commitment<K>,
+        proof: &Opening<K>,
+    ) -> bool {
+        let n = self.key.size();
+        let k = indices.len();
+        if check_subset(indices, n).is_err()
+            || k > self.max_subset_size()
+            || values.len() != k
+            || commitment.size() != n
+            || proof.size() != n
+        {
+            return false;
+        }
+
+        let points: Vec<Scalar> = indices.iter().map(|&i| self.key.element(i)).collect();
+        let interpolation = match interpolate(&points, values) {
+            Ok(interpolation) => interpolation,
+            Err(_) => return false,
+        };
+        let vanishing = vanishing_polynomial(&points);
+
+        match (
+            G1Element::multi_scalar_mul(&interpolation, &self.tau_powers_g1[..k]),
+            G2Element::multi_scalar_mul(&vanishing, &self.tau_powers_g2[..k + 1]),
+        ) {
+            (Ok(i_tau), Ok(z_tau)) => {
+                (*commitment.element() - i_tau).pairing(&G2Element::generator())
+                    == proof.element().pairing(&z_tau)
+            }
+            _ => false,
+        }
+    }
+
+    /// Verifies a proof aggregated from openings at single indices, e.g. by
+    /// [ProverKey::aggregate], against the opened values (i, v_i).
+    pub fn verify_aggregate<K: ProverKey<G = G1Element>>(
+        &self,
+        values: &[(usize, Scalar)],
+        commitment: &Commitment<K>,
+        proof: &Opening<K>,
+    ) -> bool {
+        let (indices, values): (Vec<usize>, Vec<Scalar>) = values.iter().copied().unzip();
+        self.verify_subset(&indices, &values, commitment, proof)
+    }
+}
+
+impl KeySerialization for KZGVerifierKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = KeyWriter::new(KZG_VERIFIER, self.n);
+        writer.write_g2(&self.g2_tau);
+        writer.finish()
+    }
+
+    /// The G2 point is checked to be in the subgroup, but whoever chooses [tau]_2 can make any
+    /// opening verify, so like a prover key the verifier key must be authenticated, e.g. by a
+    /// pinned digest or a signature.
+    fn from_bytes(bytes: &[u8]) -> FastCryptoResult<Self> {
+        let mut reader = KeyReader::new(bytes, KZG_VERIFIER)?;
+        let domain = reader.domain()?;
+        let g2_tau = reader.read_g2()?;
+        reader.finish()?;
+        Ok(Self::new(&domain, g2_tau))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::serde_helpers::ToFromByteArray;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::kzg_deriv::KZGDeriv;
+    use crate::kzg_fk::KZGFK;
+    use crate::kzg_original::KZGOriginal;
+    use crate::kzg_tabdfk::KZGTabDFK;
+    use crate::{ProverKey, KZG};
+
+    #[test]
+    fn test_element() {
+        let domain = BLS12381Domain::new(16).unwrap();
+        let vk = KZGVerifierKey::new(&domain, G2Element::generator());
+        for i in 0..16 {
+            assert_eq!(vk.element(i), domain.element(i));
+        }
+    }
+
+    #[test]
+    fn test_verify_with_deserialized_key() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = KZGDeriv::new(n).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let open_value = kzg.open(&v, 6).unwrap();
+
+        let bytes = kzg.verifier_key().to_bytes();
+        let vk = KZGVerifierKey::from_bytes(&bytes).unwrap();
+        assert_eq!(vk, kzg.verifier_key());
+        assert!(vk.verify(6, &v[6], &commitment, &open_value));
+        assert!(!vk.verify(5, &v[6], &commitment, &open_value));
+        assert!(!vk.verify(6 + n, &v[6], &commitment, &open_value));
+        let resized = Commitment::new(*commitment.element(), 2 * n);
+        assert!(!vk.verify(6, &v[6], &resized, &open_value));
+
+        assert!(KZGVerifierKey::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_verify_received_elements() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = KZGDeriv::new(n).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap().into_element().to_byte_array();
+        let open_value = kzg.open(&v, 6).unwrap().into_element().to_byte_array();
+
+        // A light client only gets the verifier key and the serialized points.
+        let vk = kzg.verifier_key();
+        let commitment: Commitment<KZGDeriv> =
+            vk.commitment_from_element(G1Element::from_byte_array(&commitment).unwrap());
+        let open_value = vk.opening_from_element(G1Element::from_byte_array(&open_value).unwrap());
+        assert_eq!(commitment, kzg.commit(&v).unwrap());
+        assert!(vk.verify(6, &v[6], &commitment, &open_value));
+        assert!(!vk.verify(5, &v[6], &commitment, &open_value));
+
+        let other_vk = KZGDeriv::new(2 * n).unwrap().verifier_key();
+        let resized = other_vk.commitment_from_element(*commitment.element());
+        assert!(!vk.verify(6, &v[6], &resized, &open_value));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = KZGTabDFK::new(n).unwrap();
+        let vk = kzg.verifier_key();
+        let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+        let commitment = kzg.commit(&v).unwrap();
+        let mut openings: Vec<(usize, Scalar, Opening<KZGTabDFK>)> = kzg
+            .open_all(&v)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(i, open_i)| (i, v[i], open_i))
+            .collect();
+
+        assert!(vk.verify_batch(&commitment, &openings, &mut rng));
+        assert!(vk.verify_batch(&commitment, &openings[2..5], &mut rng));
+        assert!(vk.verify_batch(&commitment, &[], &mut rng));
+
+        openings[1].2 = openings[2].2;
+        assert!(!vk.verify_batch(&commitment, &openings, &mut rng));
+        openings[1].2 = kzg.open(&v, 1).unwrap();
+        assert!(vk.verify_batch(&commitment, &openings, &mut rng));
+        openings[3].1 += Scalar::generator();
+        assert!(!vk.verify_batch(&commitment, &openings, &mut rng));
+        openings[3].0 = n;
+        assert!(!vk.verify_batch(&commitment, &openings, &mut rng));
+    }
+
+    #[test]
+    fn test_verify_batch_multi() {
+        let mut rng = thread_rng();
+        let n = 8;
+        let kzg = KZGDeriv::new(n).unwrap();
+        let vk = kzg.verifier_key();
+        let mut entries = Vec::new();
+        for i in 0..10 {
+            let v: Vec<Scalar> = (0..n).map(|_| OtherScalar::rand(&mut rng)).collect();
+            let index = i % n;
+            let commitment = kzg.commit(&v).unwrap();
+            entries.push((commitment, index, v[index], kzg.open(&v, index).unwrap()));
+        }
+
+        assert_eq!(vk.verify_batch_multi(&entries, &mut rng), Ok(()));
+        assert_eq!(vk.verify_batch_multi(&entries[..1], &mut rng), Ok(()));
+        assert_eq!(vk.verify_batch_multi::<KZGDeriv, _>(&[], &mut rng), Ok(()));
+
+        entries[3].2 += Scalar::generator();
+        entries[7].3 = entries[6].3;
+        entries[9].1 = n;
+        assert_eq!(
+            vk.verify_batch_multi(&entries, &mut rng),
+            Err(vec![3, 7, 9])
+        );
+    }
+
+    fn check_open_subset<K: KZG<G = G1Element>>(srs: &SRS, v: &[Scalar]) -> Opening<K> {
+        let n = v.len();
+        let kzg = K::from_srs(n, srs).unwrap();
+        let vk = SubsetVerifierKey::from_srs(n, srs, 4).unwrap();
+        let commitment = kzg.commit(v).unwrap();
+
+        let indices = [5, 0, 3];
+        let values: Vec<Scalar> = indices.iter().map(|&i| v[i]).collect();
+        let proof = kzg.open_subset(v, &indices).unwrap();
+        assert!(vk.verify_subset(&indices, &values, &commitment, &proof));
+        assert!(!vk.verify_subset(&[5, 0, 2], &values, &commitment, &proof));
+        assert!(!vk.verify_subset(&indices, &values[..2], &commitment, &proof));
+        let mut wrong_values = values.clone();
+        wrong_values[1] += Scalar::generator();
+        assert!(!vk.verify_subset(&indices, &wrong_values, &commitment, &proof));
+
+        // A single index gives the usual opening.
+        let open_value = kzg.open_subset(v, &[6]).unwrap();
+        assert_eq!(open_value, kzg.open(v, 6).unwrap());
+        assert!(vk.verify_subset(&[6], &v[6..7], &commitment, &open_value));
+
+        assert!(kzg.open_subset(v, &[]).is_err());
+        assert!(kzg.open_subset(v, &[1, 1]).is_err());
+        assert!(kzg.open_subset(v, &[1, n]).is_err());
+        let too_many = [0, 1, 2, 3, 4];
+        let proof = kzg.open_subset(v, &too_many).unwrap();
+        assert!(!vk.verify_subset(&too_many, &v[..5], &commitment, &proof));
+
+        kzg.open_subset(v, &indices).unwrap()
+    }
+
+    #[test]
+    fn test_open_verify_subset() {
+        let n = 8;
+        let tau = Scalar::rand(&mut thread_rng());
+        let srs = SRS::from_tau_with_g2_powers(n, 5, &tau);
+        let v: Vec<Scalar> = (0..n)
+            .map(|_| OtherScalar::rand(&mut thread_rng()))
+            .collect();
+
+        // All schemes commit to the same polynomial, so their proofs agree.
+        let proof = check_open_subset::<KZGDeriv>(&srs, &v).into_element();
+        assert_eq!(
+            check_open_subset::<KZGTabDFK>(&srs, &v).into_element(),
+            proof
+        );
+        check_open_subset::<KZGOriginal>(&srs, &v);
+        check_open_subset::<KZGFK>(&srs, &v);
+
+        // The whole domain can be opened with a zero proof.
+        let kzg = KZGDeriv::from_srs(n, &srs).unwrap();
+        let vk = SubsetVerifierKey::from_srs(n, &SRS::from_tau_with_g2_powers(n, n + 1, &tau), n)
+            .unwrap();
+        let indices: Vec<usize> = (0..n).collect();
+        let proof = kzg.open_subset(&v, &indices).unwrap();
+        assert_eq!(proof.element(), &G1Element::zero());
+        assert!(vk.verify_subset(&indices, &v, &kzg.commit(&v).unwrap(), &proof));
+    }
+
+    fn check_open_at<K: KZG<G = G1Element>>(srs: &SRS, v: &[Scalar], z: &Scalar) -> Scalar {
+        let kzg = K::from_srs(v.len(), srs).unwrap();
+        let vk = kzg.verifier_key();
+        let commitment = kzg.commit(v).unwrap();
+        let (y, proof) = kzg.open_at(v, z).unwrap();
+        assert!(vk.verify_at(z, &y, &commitment, &proof));
+        assert!(!vk.verify_at(z, &(y + Scalar::generator()), &commitment, &proof));
+        assert!(!vk.verify_at(&(z + Scalar::generator()), &y, &commitment, &proof));
+        y
+    }
+
+    #[test]
+    fn test_open_verify_at() {
+        let n = 8;
+        let tau = Scalar::rand(&mut thread_rng());
+        let srs = SRS::from_tau(n, &tau);
+        let domain = BLS12381Domain::new(n).unwrap();
+        let v: Vec<Scalar> = (0..n)
+            .map(|_| OtherScalar::rand(&mut thread_rng()))
+            .collect();
+
+        let z = Scalar::rand(&mut thread_rng());
+        let y = check_open_at::<KZGDeriv>(&srs, &v, &z);
+        assert_eq!(check_open_at::<KZGTabDFK>(&srs, &v, &z), y);
+        assert_eq!(check_open_at::<KZGOriginal>(&srs, &v, &z), y);
+        assert_eq!(check_open_at::<KZGFK>(&srs, &v, &z), y);
+
+        // Points in the domain give the entry and the usual opening.
+        let z = domain.element(5);
+        assert_eq!(check_open_at::<KZGTabDFK>(&srs, &v, &z), v[5]);
+    }
+}